@@ -0,0 +1,51 @@
+"""Export of the velox list to formats other tools can load"""
+
+import csv
+import io
+import json
+import xml.etree.ElementTree as ET
+
+
+def to_geojson(velox_dict, geocoded=()):
+    """GeoJSON FeatureCollection with a point for each velox
+
+    Veloxes in geocoded have approximate coordinates, which is told by their
+    "geocoded" property.
+    """
+    features = []
+    for el, (lat, long) in velox_dict.items():
+        # veloxes without coordinates are kept, GeoJSON allows features without a geometry
+        geometry = None
+        if lat is not None and long is not None:
+            geometry = {"type": "Point", "coordinates": [float(long), float(lat)]}
+
+        features.append({"type": "Feature", "geometry": geometry,
+                         "properties": {"name": el, "geocoded": el in geocoded}})
+
+    return json.dumps({"type": "FeatureCollection", "features": features},
+                      indent=2, ensure_ascii=False)
+
+
+def to_gpx(velox_dict):
+    """GPX file with a waypoint for each velox, veloxes without coordinates are left out"""
+    gpx = ET.Element("gpx", version="1.1", creator="luzern-velox-bot",
+                     xmlns="http://www.topografix.com/GPX/1/1")
+    for el, (lat, long) in velox_dict.items():
+        if lat is None or long is None:
+            continue
+
+        wpt = ET.SubElement(gpx, "wpt", lat=str(float(lat)), lon=str(float(long)))
+        ET.SubElement(wpt, "name").text = el
+
+    return ET.tostring(gpx, encoding="unicode", xml_declaration=True)
+
+
+def to_csv(velox_dict):
+    """POI CSV in the longitude,latitude,name layout head units import"""
+    output = io.StringIO()
+    writer = csv.writer(output, lineterminator="\n")
+    for el, (lat, long) in velox_dict.items():
+        if lat is not None and long is not None:
+            writer.writerow([float(long), float(lat), el])
+
+    return output.getvalue()
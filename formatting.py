@@ -0,0 +1,57 @@
+"""HTML formatting helpers for the messages sent with ParseMode.HTML"""
+
+import html
+import re
+
+
+def escape(text):
+    """Escape text so that Telegram shows it as-is"""
+    return html.escape(str(text), quote=False)
+
+
+def link(url, text):
+    return f"<a href='{html.escape(url)}'>{escape(text)}</a>"
+
+
+def italic(text):
+    return f"<i>{escape(text)}</i>"
+
+
+def strike(formatted):
+    """Strike through text that has already been formatted"""
+    return f"<s>{formatted}</s>"
+
+
+def strike_list_item(formatted, text):
+    """Strike through the first "- " list item linking text in a formatted message"""
+    return re.sub(rf"- (<a href='[^']*'>{re.escape(escape(text))}</a>)",
+                  lambda m: f"- {strike(m.group(1))}", formatted, count=1)
+
+
+def bullet_list(items):
+    """One "- item" line per item, each one escaped"""
+    return "".join(f"- {escape(el)}\n" for el in items)
+
+
+def dms_coordinates(lat_long_t):
+    """Coordinates in degrees, minutes and seconds, e.g. 47°03'00.7"N 8°18'33.5"E"""
+    parts = []
+    for value, directions in zip(map(float, lat_long_t), ("NS", "EW")):
+        # round first, so that 59.96 seconds carry over instead of showing as 60.0
+        minutes, seconds = divmod(round(abs(value) * 36000) / 10, 60)
+        degrees, minutes = divmod(minutes, 60)
+        parts.append(f"{degrees:.0f}°{minutes:02.0f}'{seconds:04.1f}\"{directions[value < 0]}")
+    return " ".join(parts)
+
+
+def swiss_coordinates(east, north):
+    """LV95 coordinates as written in Switzerland, e.g. 2'666'157, 1'211'386"""
+    return ", ".join(f"{x:,.0f}".replace(",", "'") for x in (east, north))
+
+
+def spoken_coordinates(lat_long_t, directions=("north", "south", "east", "west")):
+    """Coordinates spelled out for screen readers, e.g. 47.0502 north, 8.3093 east"""
+    north, south, east, west = directions
+    lat, long = (float(x) for x in lat_long_t)
+    return (f"{abs(lat):.4f} {north if lat >= 0 else south}, "
+            f"{abs(long):.4f} {east if long >= 0 else west}")
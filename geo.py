@@ -0,0 +1,91 @@
+"""Geometry helpers for location based filtering"""
+
+import math
+import xml.etree.ElementTree as ET
+
+EARTH_RADIUS_M = 6371000
+
+# points closer than this to the previously kept one are dropped from routes
+ROUTE_SIMPLIFY_M = 100
+
+
+def haversine_distance(p1, p2):
+    """Distance in meters between two (lat, long) points"""
+    lat1, long1 = map(math.radians, map(float, p1))
+    lat2, long2 = map(math.radians, map(float, p2))
+
+    a = (math.sin((lat2 - lat1) / 2) ** 2
+         + math.cos(lat1) * math.cos(lat2) * math.sin((long2 - long1) / 2) ** 2)
+    return 2 * EARTH_RADIUS_M * math.asin(math.sqrt(a))
+
+
+def wgs84_to_lv95(p):
+    """Swiss LV95 (east, north) coordinates of a (lat, long) point, within about a meter
+
+    Uses the approximate formulas published by swisstopo.
+    """
+    lat = (float(p[0]) * 3600 - 169028.66) / 10000
+    long = (float(p[1]) * 3600 - 26782.5) / 10000
+
+    east = (2600072.37 + 211455.93 * long - 10938.51 * long * lat
+            - 0.36 * long * lat ** 2 - 44.54 * long ** 3)
+    north = (1200147.07 + 308807.95 * lat + 3745.25 * long ** 2 + 76.63 * lat ** 2
+             - 194.56 * long ** 2 * lat + 119.79 * lat ** 3)
+    return east, north
+
+
+def segment_distance(p, a, b):
+    """Distance in meters between point p and the segment a-b"""
+    # project on a local plane around p, good enough for a few kilometers
+    lat0 = math.radians(float(p[0]))
+
+    def to_xy(q):
+        return (math.radians(float(q[1])) * math.cos(lat0) * EARTH_RADIUS_M,
+                math.radians(float(q[0])) * EARTH_RADIUS_M)
+
+    px, py = to_xy(p)
+    ax, ay = to_xy(a)
+    bx, by = to_xy(b)
+
+    dx, dy = bx - ax, by - ay
+    if dx == 0 and dy == 0:
+        return math.hypot(px - ax, py - ay)
+
+    t = max(0, min(1, ((px - ax) * dx + (py - ay) * dy) / (dx * dx + dy * dy)))
+    return math.hypot(px - (ax + t * dx), py - (ay + t * dy))
+
+
+def route_distance(p, route):
+    """Distance in meters between point p and a route given as a list of points"""
+    if len(route) == 1:
+        return haversine_distance(p, route[0])
+
+    return min(segment_distance(p, a, b) for a, b in zip(route, route[1:]))
+
+
+def simplify_route(route):
+    """Drop points too close to each other, keeping both ends of the route"""
+    if len(route) < 3:
+        return route
+
+    simplified = [route[0]]
+    for point in route[1:-1]:
+        if haversine_distance(simplified[-1], point) >= ROUTE_SIMPLIFY_M:
+            simplified.append(point)
+    simplified.append(route[-1])
+
+    return simplified
+
+
+def parse_gpx(data):
+    """Return the track (or route) points of a GPX file as a list of (lat, long)"""
+    root = ET.fromstring(data)
+
+    # ignore the namespace, GPX 1.0 and 1.1 use different ones
+    points = [(float(el.get('lat')), float(el.get('lon')))
+              for el in root.iter() if el.tag.rsplit('}', 1)[-1] == 'trkpt']
+    if not points:
+        points = [(float(el.get('lat')), float(el.get('lon')))
+                  for el in root.iter() if el.tag.rsplit('}', 1)[-1] == 'rtept']
+
+    return points
@@ -0,0 +1,87 @@
+"""HTTP server: /healthz for container probes, the POI files and the changes feed for other bots"""
+
+import json
+import logging
+import secrets
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from urllib.parse import parse_qs, urlsplit
+
+http_log = logging.getLogger("velox.http")
+
+# page size of the /changes endpoint
+CHANGES_DEFAULT_LIMIT = 100
+CHANGES_MAX_LIMIT = 1000
+
+
+def start_http_server(port, configs, get_health, get_changes_since, poi_dir, poi_types):
+    """Serve in a background thread
+
+    configs is read on each request, so tokens changed by /admin_config apply
+    right away. poi_types maps the files in poi_dir to their content type.
+    """
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            url = urlsplit(self.path)
+            query = {k: v[0] for k, v in parse_qs(url.query).items()}
+            if url.path == "/healthz":
+                health = get_health()
+                self.send_body(200 if health["ok"] else 503, "application/json",
+                               json.dumps(health).encode())
+            elif url.path.startswith("/poi/") and url.path[5:] in poi_types:
+                if self.authorized("POI_TOKEN", query):
+                    self.send_poi(url.path[5:])
+            elif url.path == "/changes":
+                if self.authorized("CHANGES_TOKEN", query):
+                    self.send_changes(query)
+            else:
+                self.send_error(404)
+
+        def authorized(self, config, query):
+            """Whether the token matches the one in config.json, serving is off without one"""
+            token = configs.get(config)
+            if not token or not secrets.compare_digest(query.get("token", ""), token):
+                self.send_error(403)
+                return False
+            return True
+
+        def send_changes(self, query):
+            try:
+                since = int(query.get("since", 0))
+                limit = min(int(query.get("limit", CHANGES_DEFAULT_LIMIT)), CHANGES_MAX_LIMIT)
+                if since < 0 or limit < 1:
+                    raise ValueError
+            except ValueError:
+                self.send_error(400, "since and limit must be positive numbers")
+                return
+
+            changes = get_changes_since(since, limit)
+            body = {"changes": changes,
+                    # pass it as since to get the following page, or the next changes
+                    "next_cursor": changes[-1]["cursor"] if changes else since}
+            self.send_body(200, "application/json", json.dumps(body, ensure_ascii=False).encode())
+
+        def send_poi(self, name):
+            try:
+                with open(f'{poi_dir}/{name}', 'rb') as f:
+                    body = f.read()
+            except FileNotFoundError:
+                self.send_error(404)
+                return
+
+            self.send_body(200, poi_types[name], body)
+
+        def send_body(self, status, content_type, body):
+            self.send_response(status)
+            self.send_header("Content-Type", content_type)
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            # probes come every few seconds, don't flood the log
+            pass
+
+    server = ThreadingHTTPServer(("", port), Handler)
+    threading.Thread(target=server.serve_forever, name="http", daemon=True).start()
+    http_log.info(f"Serving HTTP on port {port}")
@@ -0,0 +1,760 @@
+"""Translations of the user-facing texts, keyed by their English version"""
+
+# language of the chats that subscribe without a supported Telegram language
+DEFAULT_LANGUAGE = "de"
+
+LANGUAGES = {"de": "Deutsch", "en": "English", "fr": "Français", "it": "Italiano"}
+
+WEEKDAYS = ("Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday")
+
+TRANSLATIONS = {
+    "de": {
+        "Checking for updates\n\n": "Suche nach Änderungen\n\n",
+        "Added:\n": "Neu:\n",
+        "Removed:\n": "Entfernt:\n",
+        "🟢 Removed:\n": "🟢 Entfernt:\n",
+        "Just outside your route ({distance}):\n": "Knapp ausserhalb deiner Route ({distance}):\n",
+        "({distance} away)": "({distance} entfernt)",
+        "No changes detected.": "Keine Änderungen festgestellt.",
+        "Failed to fetch updates.": "Abruf der Änderungen fehlgeschlagen.",
+        "Current List\n\n": "Aktuelle Liste\n\n",
+        "Recent changes\n\n": "Letzte Änderungen\n\n",
+        "No changes recorded yet.": "Noch keine Änderungen aufgezeichnet.",
+        "🧪 Test message, nothing actually changed.": "🧪 Testnachricht, es hat sich nichts geändert.",
+        "Last updated: {time}": "Zuletzt aktualisiert: {time}",
+        "The police website can't be reached, this is the list of {time}.":
+            "Die Polizei-Website ist nicht erreichbar, das ist die Liste vom {time}.",
+        "Update at {time}": "Änderung um {time}",
+        "today": "heute",
+        "Monday": "Montag",
+        "Tuesday": "Dienstag",
+        "Wednesday": "Mittwoch",
+        "Thursday": "Donnerstag",
+        "Friday": "Freitag",
+        "Saturday": "Samstag",
+        "Sunday": "Sonntag",
+        ", new today": ", heute neu",
+        ", new since {day}": ", seit {day} neu",
+        "…and {count} more veloxes changed while you were at your hourly limit, "
+        "see /current_list":
+            "…und {count} weitere Veloxe haben sich geändert, während dein Stundenlimit "
+            "erreicht war, siehe /current_list",
+        ", at {coordinates}": ", bei {coordinates}",
+        ", followed": ", verfolgt",
+        " ≈ {distance} from you": " ≈ {distance} von dir",
+        ", about {distance} from you": ", etwa {distance} von dir",
+        "north": "Nord",
+        "south": "Süd",
+        "east": "Ost",
+        "west": "West",
+        "meters": "Meter",
+        "kilometers": "Kilometer",
+        "feet": "Fuss",
+        "miles": "Meilen",
+        "🔇 mute {velox}": "🔇 {velox} stummschalten",
+        "Mute {velox}": "{velox} stummschalten",
+        "Muted {velox}. Use /unmute to undo.": "{velox} stummgeschaltet. Mit /unmute rückgängig machen.",
+        "You're subscribed to updates.": "Du hast die Velox-Meldungen abonniert.",
+        "Already subscribed.": "Bereits abonniert.",
+        "Tap a setting to toggle it:": "Tippe auf eine Einstellung, um sie umzuschalten:",
+        "Tap a category to snooze or unsnooze it:":
+            "Tippe auf eine Kategorie, um sie stumm oder wieder laut zu schalten:",
+        "{label} snoozed": "{label} stummgeschaltet",
+        "{label} unsnoozed": "{label} wieder aktiv",
+        "Removed veloxes": "Entfernte Veloxe",
+        "Veloxes just outside your route": "Veloxe knapp ausserhalb deiner Route",
+        "Failed update checks": "Fehlgeschlagene Abrufe",
+        "Notify when nothing changed": "Auch ohne Änderungen melden",
+        "Enabled - get status updates even if no changes are detected":
+            "Aktiviert - du bekommst auch ohne Änderungen eine Statusmeldung",
+        "Disabled - no status updates if no changes are detected":
+            "Deaktiviert - keine Statusmeldung ohne Änderungen",
+        "Strike through removed veloxes": "Entfernte Veloxe durchstreichen",
+        "Enabled - removed veloxes get struck through in previous updates":
+            "Aktiviert - entfernte Veloxe werden in früheren Meldungen durchgestrichen",
+        "Disabled - previous updates are left untouched":
+            "Deaktiviert - frühere Meldungen bleiben unverändert",
+        "Merge updates of the same day": "Meldungen desselben Tages zusammenfassen",
+        "Enabled - updates of the same day are merged into a single message":
+            "Aktiviert - Meldungen desselben Tages werden in einer Nachricht zusammengefasst",
+        "Disabled - every update is sent as a new message":
+            "Deaktiviert - jede Meldung kommt als neue Nachricht",
+        "Also send new veloxes as map pins": "Neue Veloxe auch als Kartenpins senden",
+        "Enabled - new veloxes also come as map pins you can open in any navigation app":
+            "Aktiviert - neue Veloxe kommen auch als Kartenpins, die du in jeder Navi-App "
+            "öffnen kannst",
+        "Disabled - new veloxes only come as links": "Deaktiviert - neue Veloxe kommen nur als Links",
+        "Screen reader friendly messages": "Screenreader-freundliche Nachrichten",
+        "Enabled - updates avoid emoji and spell out coordinates and distances":
+            "Aktiviert - Meldungen ohne Emoji, mit ausgeschriebenen Koordinaten und Distanzen",
+        "Disabled - updates use the compact layout": "Deaktiviert - Meldungen im kompakten Layout",
+        "New setting available: {label} (currently {state})":
+            "Neue Einstellung verfügbar: {label} (derzeit {state})",
+        "on": "ein",
+        "off": "aus",
+        "Change it in /settings.": "Ändern kannst du sie in /settings.",
+        "Choose your language:": "Wähle deine Sprache:",
+        "Language set to {name}.": "Sprache auf {name} gestellt.",
+        "Failed to fetch the current list, please try again later.":
+            "Abruf der aktuellen Liste fehlgeschlagen, bitte versuche es später nochmals.",
+        "none": "nicht gesetzt",
+        "You're not subscribed.": "Du bist nicht abonniert.",
+        "Please subscribe with /start first.": "Bitte abonniere zuerst mit /start.",
+        "Too many commands, please try again in {minutes} minutes.":
+            "Zu viele Befehle, bitte versuche es in {minutes} Minuten nochmals.",
+        "Velox map": "Velox-Karte",
+        "An update check is already running, please try again in a moment.":
+            "Es läuft bereits eine Prüfung auf Änderungen, bitte versuche es gleich nochmals.",
+        "Speed camera": "Blitzer",
+        ", {distance} away": ", {distance} entfernt",
+        "🧭 Navigate": "🧭 Navigieren",
+        "This velox is no longer listed.": "Dieser Velox ist nicht mehr aufgeführt.",
+        "Disabled - updates are sent as new messages again":
+            "Deaktiviert - Meldungen kommen wieder als neue Nachrichten",
+        "Enabled - the pinned list is edited on every change":
+            "Aktiviert - die angeheftete Liste wird bei jeder Änderung bearbeitet",
+        "Could not pin the list. Make sure I'm allowed to pin messages.":
+            "Die Liste konnte nicht angeheftet werden. Stelle sicher, dass ich Nachrichten "
+            "anheften darf.",
+        "Usage: /follow &lt;velox name&gt;\n\n": "Verwendung: /follow &lt;Velox-Name&gt;\n\n",
+        "Followed veloxes:\n": "Verfolgte Veloxe:\n",
+        "You're not following any velox.": "Du verfolgst keinen Velox.",
+        "No velox matching '{query}' in the current list.":
+            "Kein Velox passt zu '{query}' in der aktuellen Liste.",
+        "Multiple veloxes match, please be more specific:\n":
+            "Mehrere Veloxe passen, bitte sei genauer:\n",
+        "Already following {velox}.": "{velox} wird bereits verfolgt.",
+        "Following {velox} - it will be marked with ⭐ in updates.":
+            "{velox} wird verfolgt - er wird in Meldungen mit ⭐ markiert.",
+        "Usage: /unfollow &lt;followed velox name&gt;":
+            "Verwendung: /unfollow &lt;Name eines verfolgten Velox&gt;",
+        "Multiple followed veloxes match, please be more specific:\n":
+            "Mehrere verfolgte Veloxe passen, bitte sei genauer:\n",
+        "No longer following {velox}.": "{velox} wird nicht mehr verfolgt.",
+        "Usage: /unmute &lt;muted velox name&gt;\n\n":
+            "Verwendung: /unmute &lt;Name eines stummgeschalteten Velox&gt;\n\n",
+        "Muted veloxes:\n": "Stummgeschaltete Veloxe:\n",
+        "You haven't muted any velox.": "Du hast keinen Velox stummgeschaltet.",
+        "Multiple muted veloxes match, please be more specific:\n":
+            "Mehrere stummgeschaltete Veloxe passen, bitte sei genauer:\n",
+        "Unmuted {velox}.": "{velox} ist nicht mehr stummgeschaltet.",
+        "No veloxes within {distance} of this location.":
+            "Keine Veloxe im Umkreis von {distance} um diesen Standort.",
+        "Veloxes within {distance}\n\n": "Veloxe im Umkreis von {distance}\n\n",
+        "/nearest lists the veloxes closest to your home location, save it with /set_home first. "
+        "Or share any location to see the veloxes around it.":
+            "/nearest zeigt die Veloxe, die deinem Zuhause am nächsten sind. Speichere es zuerst "
+            "mit /set_home. Oder teile einen beliebigen Standort, um die Veloxe in der Nähe zu "
+            "sehen.",
+        "Usage: /nearest [count] [map]\ne.g. /nearest 5 map also sends the 5 veloxes as map pins":
+            "Verwendung: /nearest [Anzahl] [map]\nz.B. /nearest 5 map sendet die 5 Veloxe auch "
+            "als Kartenpins",
+        "No veloxes with known coordinates right now.":
+            "Zurzeit keine Veloxe mit bekannten Koordinaten.",
+        "Closest veloxes to your home\n\n": "Die deinem Zuhause nächsten Veloxe\n\n",
+        "Couldn't find any track in this GPX file.":
+            "In dieser GPX-Datei wurde keine Strecke gefunden.",
+        "Route saved - you'll only be notified about veloxes within {distance} of it.\nUse "
+        "/route_distance to change the distance and /clear_route to remove the route.":
+            "Route gespeichert - du wirst nur über Veloxe im Umkreis von {distance} informiert.\n"
+            "Mit /route_distance änderst du die Distanz, mit /clear_route entfernst du die Route.",
+        "Usage: /commute <home lat,long> <work lat,long>\ne.g. /commute 47.0502,8.3093 "
+        "47.0165,8.3149":
+            "Verwendung: /commute <Zuhause Breite,Länge> <Arbeit Breite,Länge>\nz.B. /commute "
+            "47.0502,8.3093 47.0165,8.3149",
+        "Couldn't compute a route between these points.":
+            "Zwischen diesen Punkten konnte keine Route berechnet werden.",
+        "Commute saved - you'll only be notified about veloxes within {distance} of it.\nUse "
+        "/route_distance to change the distance and /clear_route to remove the route.":
+            "Arbeitsweg gespeichert - du wirst nur über Veloxe im Umkreis von {distance} "
+            "informiert.\nMit /route_distance änderst du die Distanz, mit /clear_route entfernst "
+            "du die Route.",
+        "Usage: /route_distance <meters>\n\nCurrent distance: {distance}":
+            "Verwendung: /route_distance <Meter>\n\nAktuelle Distanz: {distance}",
+        "Veloxes within {distance} of your route will be notified.":
+            "Veloxe im Umkreis von {distance} deiner Route werden gemeldet.",
+        "Usage: /soft_distance <meters>\nMust be more than your route distance ({distance}), 0 "
+        "disables it.\n\nCurrent soft distance: {current}":
+            "Verwendung: /soft_distance <Meter>\nMuss grösser als deine Routendistanz "
+            "({distance}) sein, 0 deaktiviert sie.\n\nAktuelle weiche Distanz: {current}",
+        "Disabled - veloxes outside your route are never notified":
+            "Deaktiviert - Veloxe ausserhalb deiner Route werden nie gemeldet",
+        "Enabled - once a day you'll hear about veloxes up to {distance} from your route":
+            "Aktiviert - einmal am Tag erfährst du von Veloxen bis {distance} neben deiner Route",
+        "No route saved. Send me a GPX file or use /commute to set one.":
+            "Keine Route gespeichert. Schick mir eine GPX-Datei oder verwende /commute.",
+        "Route removed - you'll be notified about all veloxes again.":
+            "Route entfernt - du wirst wieder über alle Veloxe informiert.",
+        "Home location saved. Use /set_radius to only get veloxes around it.":
+            "Zuhause gespeichert. Mit /set_radius bekommst du nur noch Veloxe in der Nähe.",
+        "Home location saved - you'll only be notified about veloxes within {distance} of it.":
+            "Zuhause gespeichert - du wirst nur über Veloxe im Umkreis von {distance} informiert.",
+        "Usage: /set_home [lat,long]\ne.g. /set_home 47.0502,8.3093":
+            "Verwendung: /set_home [Breite,Länge]\nz.B. /set_home 47.0502,8.3093",
+        "Share your home location (📎 > Location) and I'll remember it.":
+            "Teile deinen Standort zu Hause (📎 > Standort), ich merke ihn mir.",
+        "Usage: /set_radius <km>\n0 disables the filter.\n\nCurrent radius: {current}":
+            "Verwendung: /set_radius <km>\n0 deaktiviert den Filter.\n\nAktueller Radius: "
+            "{current}",
+        "Disabled - veloxes are notified wherever they are":
+            "Deaktiviert - Veloxe werden überall gemeldet",
+        "Radius set to {distance}. Use /set_home to tell me where home is.":
+            "Radius auf {distance} gesetzt. Sag mir mit /set_home, wo du zu Hause bist.",
+        "Enabled - only veloxes within {distance} of home are notified":
+            "Aktiviert - nur Veloxe im Umkreis von {distance} um dein Zuhause werden gemeldet",
+        "Usage: /filter_area &lt;municipality&gt; adds or removes a municipality, /filter_area "
+        "all removes the filter\n\n":
+            "Verwendung: /filter_area &lt;Gemeinde&gt; fügt eine Gemeinde hinzu oder entfernt "
+            "sie, /filter_area all entfernt den Filter\n\n",
+        "Only veloxes in these municipalities are notified:\n":
+            "Nur Veloxe in diesen Gemeinden werden gemeldet:\n",
+        "Veloxes of every municipality are notified.": "Veloxe aller Gemeinden werden gemeldet.",
+        "Veloxes of every municipality will be notified.":
+            "Veloxe aller Gemeinden werden gemeldet.",
+        "Removed {area} from your municipalities.": "{area} aus deinen Gemeinden entfernt.",
+        "Added {area} to your municipalities.": "{area} zu deinen Gemeinden hinzugefügt.",
+        "There is currently no velox listed there.": "Dort ist zurzeit kein Velox aufgeführt.",
+        "Usage: /max_per_hour <messages>\n0 removes the limit.\n\nCurrent limit: {current}":
+            "Verwendung: /max_per_hour <Nachrichten>\n0 entfernt das Limit.\n\nAktuelles Limit: "
+            "{current}",
+        "Disabled - you'll get every update as it comes":
+            "Deaktiviert - du bekommst jede Meldung sofort",
+        "Enabled - at most {cap} updates per hour, the rest is summarized in a single message":
+            "Aktiviert - höchstens {cap} Meldungen pro Stunde, der Rest wird in einer Nachricht "
+            "zusammengefasst",
+        "Usage: /no_updates_every <hours>\n0 sends one after every check, 24 about once a day. "
+        "Only applies with /notify_no_updates enabled.\n\nCurrent interval: {current}":
+            "Verwendung: /no_updates_every <Stunden>\n0 schickt eine nach jedem Abruf, 24 etwa "
+            "einmal am Tag. Gilt nur mit aktiviertem /notify_no_updates.\n\nAktuelles Intervall: "
+            "{current}",
+        "{hours} hours": "{hours} Stunden",
+        "every check": "jeder Abruf",
+        "You'll get a \"no changes\" message after every check":
+            "Du bekommst nach jedem Abruf eine Nachricht \"keine Änderungen\"",
+        "You'll get at most one \"no changes\" message every {hours} hours":
+            "Du bekommst höchstens alle {hours} Stunden eine Nachricht \"keine Änderungen\"",
+        "Usage: /timezone <name>\ne.g. /timezone Europe/Zurich, times in your messages are then "
+        "shown in that timezone.\n\nCurrent timezone: {current}":
+            "Verwendung: /timezone <Name>\nz.B. /timezone Europe/Zurich, die Zeiten in deinen "
+            "Nachrichten werden dann in dieser Zeitzone angezeigt.\n\nAktuelle Zeitzone: {current}",
+        "the bot's server time": "die Serverzeit des Bots",
+        "Times are now shown in {timezone}, currently {time}":
+            "Zeiten werden jetzt in {timezone} angezeigt, aktuell {time}",
+        "Usage: /coordinates <format>|off\n": "Verwendung: /coordinates <Format>|off\n",
+        "decimal degrees, e.g. 47.05020, 8.30930": "Dezimalgrad, z.B. 47.05020, 8.30930",
+        "degrees, minutes and seconds, e.g. 47°03'00.7\"N 8°18'33.5\"E":
+            "Grad, Minuten und Sekunden, z.B. 47°03'00.7\"N 8°18'33.5\"E",
+        "Swiss LV95, e.g. 2'666'157, 1'211'386": "Schweizer LV95, z.B. 2'666'157, 1'211'386",
+        "Current format: {current}": "Aktuelles Format: {current}",
+        "Disabled - veloxes only come as links": "Deaktiviert - Veloxe kommen nur als Links",
+        "Enabled - veloxes come with their coordinates in {format}":
+            "Aktiviert - Veloxe kommen mit ihren Koordinaten im Format {format}",
+        "Usage: /units {units}\n\nCurrent units: {current}":
+            "Verwendung: /units {units}\n\nAktuelle Einheiten: {current}",
+        "Distances are now shown in {units} units":
+            "Distanzen werden jetzt in {units} Einheiten angezeigt",
+    },
+    "fr": {
+        "Checking for updates\n\n": "Recherche de mises à jour\n\n",
+        "Added:\n": "Ajoutés :\n",
+        "Removed:\n": "Retirés :\n",
+        "🟢 Removed:\n": "🟢 Retirés :\n",
+        "Just outside your route ({distance}):\n":
+            "Juste en dehors de ton itinéraire ({distance}) :\n",
+        "({distance} away)": "(à {distance})",
+        "No changes detected.": "Aucun changement détecté.",
+        "Failed to fetch updates.": "Impossible de récupérer les mises à jour.",
+        "Current List\n\n": "Liste actuelle\n\n",
+        "Recent changes\n\n": "Derniers changements\n\n",
+        "No changes recorded yet.": "Aucun changement enregistré pour l'instant.",
+        "🧪 Test message, nothing actually changed.": "🧪 Message de test, rien n'a changé.",
+        "Last updated: {time}": "Dernière mise à jour : {time}",
+        "The police website can't be reached, this is the list of {time}.":
+            "Le site de la police est injoignable, voici la liste du {time}.",
+        "Update at {time}": "Mise à jour à {time}",
+        "today": "aujourd'hui",
+        "Monday": "lundi",
+        "Tuesday": "mardi",
+        "Wednesday": "mercredi",
+        "Thursday": "jeudi",
+        "Friday": "vendredi",
+        "Saturday": "samedi",
+        "Sunday": "dimanche",
+        ", new today": ", nouveau aujourd'hui",
+        ", new since {day}": ", nouveau depuis {day}",
+        "…and {count} more veloxes changed while you were at your hourly limit, "
+        "see /current_list":
+            "…et {count} autres radars ont changé pendant que ta limite horaire était "
+            "atteinte, voir /current_list",
+        ", at {coordinates}": ", à {coordinates}",
+        ", followed": ", suivi",
+        " ≈ {distance} from you": " ≈ {distance} de toi",
+        ", about {distance} from you": ", à environ {distance} de toi",
+        "north": "nord",
+        "south": "sud",
+        "east": "est",
+        "west": "ouest",
+        "meters": "mètres",
+        "kilometers": "kilomètres",
+        "feet": "pieds",
+        "miles": "miles",
+        "🔇 mute {velox}": "🔇 masquer {velox}",
+        "Mute {velox}": "Masquer {velox}",
+        "Muted {velox}. Use /unmute to undo.": "{velox} masqué. Utilise /unmute pour annuler.",
+        "You're subscribed to updates.": "Tu es abonné aux mises à jour.",
+        "Already subscribed.": "Déjà abonné.",
+        "Tap a setting to toggle it:": "Touche un réglage pour l'activer ou le désactiver :",
+        "Tap a category to snooze or unsnooze it:":
+            "Touche une catégorie pour la mettre en sourdine ou la réactiver :",
+        "{label} snoozed": "{label} en sourdine",
+        "{label} unsnoozed": "{label} réactivés",
+        "Removed veloxes": "Radars retirés",
+        "Veloxes just outside your route": "Radars juste en dehors de ton itinéraire",
+        "Failed update checks": "Vérifications échouées",
+        "Notify when nothing changed": "Notifier même sans changement",
+        "Enabled - get status updates even if no changes are detected":
+            "Activé - tu reçois un statut même sans changement",
+        "Disabled - no status updates if no changes are detected":
+            "Désactivé - pas de statut sans changement",
+        "Strike through removed veloxes": "Barrer les radars retirés",
+        "Enabled - removed veloxes get struck through in previous updates":
+            "Activé - les radars retirés sont barrés dans les messages précédents",
+        "Disabled - previous updates are left untouched":
+            "Désactivé - les messages précédents restent inchangés",
+        "Merge updates of the same day": "Regrouper les mises à jour du même jour",
+        "Enabled - updates of the same day are merged into a single message":
+            "Activé - les mises à jour du même jour sont regroupées dans un seul message",
+        "Disabled - every update is sent as a new message":
+            "Désactivé - chaque mise à jour arrive dans un nouveau message",
+        "Also send new veloxes as map pins": "Envoyer aussi les nouveaux radars comme épingles",
+        "Enabled - new veloxes also come as map pins you can open in any navigation app":
+            "Activé - les nouveaux radars arrivent aussi comme épingles à ouvrir dans "
+            "ton application de navigation",
+        "Disabled - new veloxes only come as links":
+            "Désactivé - les nouveaux radars arrivent seulement comme liens",
+        "Screen reader friendly messages": "Messages adaptés aux lecteurs d'écran",
+        "Enabled - updates avoid emoji and spell out coordinates and distances":
+            "Activé - les mises à jour évitent les emoji et écrivent en toutes lettres "
+            "coordonnées et distances",
+        "Disabled - updates use the compact layout":
+            "Désactivé - les mises à jour utilisent la mise en page compacte",
+        "New setting available: {label} (currently {state})":
+            "Nouveau réglage disponible : {label} (actuellement {state})",
+        "on": "activé",
+        "off": "désactivé",
+        "Change it in /settings.": "Tu peux le changer dans /settings.",
+        "Choose your language:": "Choisis ta langue :",
+        "Language set to {name}.": "Langue réglée sur {name}.",
+        "Failed to fetch the current list, please try again later.":
+            "Impossible de récupérer la liste actuelle, réessaie plus tard.",
+        "none": "non défini",
+        "You're not subscribed.": "Tu n'es pas abonné.",
+        "Please subscribe with /start first.": "Abonne-toi d'abord avec /start.",
+        "Too many commands, please try again in {minutes} minutes.":
+            "Trop de commandes, réessaie dans {minutes} minutes.",
+        "Velox map": "Carte des radars",
+        "An update check is already running, please try again in a moment.":
+            "Une vérification est déjà en cours, réessaie dans un instant.",
+        "Speed camera": "Radar",
+        ", {distance} away": ", à {distance}",
+        "🧭 Navigate": "🧭 Itinéraire",
+        "This velox is no longer listed.": "Ce radar n'est plus dans la liste.",
+        "Disabled - updates are sent as new messages again":
+            "Désactivé - les mises à jour arrivent de nouveau comme nouveaux messages",
+        "Enabled - the pinned list is edited on every change":
+            "Activé - la liste épinglée est modifiée à chaque changement",
+        "Could not pin the list. Make sure I'm allowed to pin messages.":
+            "Impossible d'épingler la liste. Vérifie que j'ai le droit d'épingler des messages.",
+        "Usage: /follow &lt;velox name&gt;\n\n": "Utilisation : /follow &lt;nom du radar&gt;\n\n",
+        "Followed veloxes:\n": "Radars suivis :\n",
+        "You're not following any velox.": "Tu ne suis aucun radar.",
+        "No velox matching '{query}' in the current list.":
+            "Aucun radar ne correspond à '{query}' dans la liste actuelle.",
+        "Multiple veloxes match, please be more specific:\n":
+            "Plusieurs radars correspondent, sois plus précis :\n",
+        "Already following {velox}.": "Tu suis déjà {velox}.",
+        "Following {velox} - it will be marked with ⭐ in updates.":
+            "Tu suis {velox} - il sera marqué d'une ⭐ dans les mises à jour.",
+        "Usage: /unfollow &lt;followed velox name&gt;":
+            "Utilisation : /unfollow &lt;nom d'un radar suivi&gt;",
+        "Multiple followed veloxes match, please be more specific:\n":
+            "Plusieurs radars suivis correspondent, sois plus précis :\n",
+        "No longer following {velox}.": "Tu ne suis plus {velox}.",
+        "Usage: /unmute &lt;muted velox name&gt;\n\n":
+            "Utilisation : /unmute &lt;nom d'un radar masqué&gt;\n\n",
+        "Muted veloxes:\n": "Radars masqués :\n",
+        "You haven't muted any velox.": "Tu n'as masqué aucun radar.",
+        "Multiple muted veloxes match, please be more specific:\n":
+            "Plusieurs radars masqués correspondent, sois plus précis :\n",
+        "Unmuted {velox}.": "{velox} n'est plus masqué.",
+        "No veloxes within {distance} of this location.":
+            "Aucun radar à moins de {distance} de cet endroit.",
+        "Veloxes within {distance}\n\n": "Radars à moins de {distance}\n\n",
+        "/nearest lists the veloxes closest to your home location, save it with /set_home first. "
+        "Or share any location to see the veloxes around it.":
+            "/nearest liste les radars les plus proches de ton domicile, enregistre-le d'abord "
+            "avec /set_home. Ou partage n'importe quelle position pour voir les radars autour.",
+        "Usage: /nearest [count] [map]\ne.g. /nearest 5 map also sends the 5 veloxes as map pins":
+            "Utilisation : /nearest [nombre] [map]\nex. /nearest 5 map envoie aussi les 5 radars "
+            "comme épingles",
+        "No veloxes with known coordinates right now.":
+            "Aucun radar avec des coordonnées connues pour le moment.",
+        "Closest veloxes to your home\n\n": "Radars les plus proches de ton domicile\n\n",
+        "Couldn't find any track in this GPX file.": "Aucun tracé trouvé dans ce fichier GPX.",
+        "Route saved - you'll only be notified about veloxes within {distance} of it.\nUse "
+        "/route_distance to change the distance and /clear_route to remove the route.":
+            "Itinéraire enregistré - tu ne seras notifié que des radars à moins de {distance}.\n"
+            "Utilise /route_distance pour changer la distance et /clear_route pour supprimer "
+            "l'itinéraire.",
+        "Usage: /commute <home lat,long> <work lat,long>\ne.g. /commute 47.0502,8.3093 "
+        "47.0165,8.3149":
+            "Utilisation : /commute <domicile lat,long> <travail lat,long>\nex. /commute "
+            "47.0502,8.3093 47.0165,8.3149",
+        "Couldn't compute a route between these points.":
+            "Impossible de calculer un itinéraire entre ces points.",
+        "Commute saved - you'll only be notified about veloxes within {distance} of it.\nUse "
+        "/route_distance to change the distance and /clear_route to remove the route.":
+            "Trajet enregistré - tu ne seras notifié que des radars à moins de {distance}.\n"
+            "Utilise /route_distance pour changer la distance et /clear_route pour supprimer "
+            "l'itinéraire.",
+        "Usage: /route_distance <meters>\n\nCurrent distance: {distance}":
+            "Utilisation : /route_distance <mètres>\n\nDistance actuelle : {distance}",
+        "Veloxes within {distance} of your route will be notified.":
+            "Les radars à moins de {distance} de ton itinéraire seront notifiés.",
+        "Usage: /soft_distance <meters>\nMust be more than your route distance ({distance}), 0 "
+        "disables it.\n\nCurrent soft distance: {current}":
+            "Utilisation : /soft_distance <mètres>\nDoit dépasser la distance de ton itinéraire "
+            "({distance}), 0 la désactive.\n\nDistance souple actuelle : {current}",
+        "Disabled - veloxes outside your route are never notified":
+            "Désactivé - les radars hors de ton itinéraire ne sont jamais notifiés",
+        "Enabled - once a day you'll hear about veloxes up to {distance} from your route":
+            "Activé - une fois par jour, tu apprends les radars jusqu'à {distance} de ton "
+            "itinéraire",
+        "No route saved. Send me a GPX file or use /commute to set one.":
+            "Aucun itinéraire enregistré. Envoie-moi un fichier GPX ou utilise /commute.",
+        "Route removed - you'll be notified about all veloxes again.":
+            "Itinéraire supprimé - tu seras de nouveau notifié de tous les radars.",
+        "Home location saved. Use /set_radius to only get veloxes around it.":
+            "Domicile enregistré. Utilise /set_radius pour ne recevoir que les radars autour.",
+        "Home location saved - you'll only be notified about veloxes within {distance} of it.":
+            "Domicile enregistré - tu ne seras notifié que des radars à moins de {distance}.",
+        "Usage: /set_home [lat,long]\ne.g. /set_home 47.0502,8.3093":
+            "Utilisation : /set_home [lat,long]\nex. /set_home 47.0502,8.3093",
+        "Share your home location (📎 > Location) and I'll remember it.":
+            "Partage la position de ton domicile (📎 > Position) et je la retiendrai.",
+        "Usage: /set_radius <km>\n0 disables the filter.\n\nCurrent radius: {current}":
+            "Utilisation : /set_radius <km>\n0 désactive le filtre.\n\nRayon actuel : {current}",
+        "Disabled - veloxes are notified wherever they are":
+            "Désactivé - les radars sont notifiés où qu'ils soient",
+        "Radius set to {distance}. Use /set_home to tell me where home is.":
+            "Rayon réglé sur {distance}. Utilise /set_home pour m'indiquer ton domicile.",
+        "Enabled - only veloxes within {distance} of home are notified":
+            "Activé - seuls les radars à moins de {distance} de ton domicile sont notifiés",
+        "Usage: /filter_area &lt;municipality&gt; adds or removes a municipality, /filter_area "
+        "all removes the filter\n\n":
+            "Utilisation : /filter_area &lt;commune&gt; ajoute ou retire une commune, "
+            "/filter_area all supprime le filtre\n\n",
+        "Only veloxes in these municipalities are notified:\n":
+            "Seuls les radars de ces communes sont notifiés :\n",
+        "Veloxes of every municipality are notified.":
+            "Les radars de toutes les communes sont notifiés.",
+        "Veloxes of every municipality will be notified.":
+            "Les radars de toutes les communes seront notifiés.",
+        "Removed {area} from your municipalities.": "{area} retirée de tes communes.",
+        "Added {area} to your municipalities.": "{area} ajoutée à tes communes.",
+        "There is currently no velox listed there.": "Aucun radar n'y est listé pour le moment.",
+        "Usage: /max_per_hour <messages>\n0 removes the limit.\n\nCurrent limit: {current}":
+            "Utilisation : /max_per_hour <messages>\n0 supprime la limite.\n\nLimite actuelle : "
+            "{current}",
+        "Disabled - you'll get every update as it comes":
+            "Désactivé - tu reçois chaque mise à jour dès qu'elle arrive",
+        "Enabled - at most {cap} updates per hour, the rest is summarized in a single message":
+            "Activé - au plus {cap} mises à jour par heure, le reste est résumé dans un seul "
+            "message",
+        "Usage: /no_updates_every <hours>\n0 sends one after every check, 24 about once a day. "
+        "Only applies with /notify_no_updates enabled.\n\nCurrent interval: {current}":
+            "Utilisation : /no_updates_every <heures>\n0 en envoie un après chaque vérification, "
+            "24 environ une fois par jour. Ne s'applique qu'avec /notify_no_updates activé.\n\n"
+            "Intervalle actuel : {current}",
+        "{hours} hours": "{hours} heures",
+        "every check": "chaque vérification",
+        "You'll get a \"no changes\" message after every check":
+            "Tu recevras un message « aucun changement » après chaque vérification",
+        "You'll get at most one \"no changes\" message every {hours} hours":
+            "Tu recevras au plus un message « aucun changement » toutes les {hours} heures",
+        "Usage: /timezone <name>\ne.g. /timezone Europe/Zurich, times in your messages are then "
+        "shown in that timezone.\n\nCurrent timezone: {current}":
+            "Utilisation : /timezone <nom>\nex. /timezone Europe/Zurich, les heures de tes "
+            "messages sont alors affichées dans ce fuseau horaire.\n\nFuseau horaire actuel : "
+            "{current}",
+        "the bot's server time": "l'heure du serveur du bot",
+        "Times are now shown in {timezone}, currently {time}":
+            "Les heures sont maintenant affichées en {timezone}, il est {time}",
+        "Usage: /coordinates <format>|off\n": "Utilisation : /coordinates <format>|off\n",
+        "decimal degrees, e.g. 47.05020, 8.30930": "degrés décimaux, ex. 47.05020, 8.30930",
+        "degrees, minutes and seconds, e.g. 47°03'00.7\"N 8°18'33.5\"E":
+            "degrés, minutes et secondes, ex. 47°03'00.7\"N 8°18'33.5\"E",
+        "Swiss LV95, e.g. 2'666'157, 1'211'386": "LV95 suisse, ex. 2'666'157, 1'211'386",
+        "Current format: {current}": "Format actuel : {current}",
+        "Disabled - veloxes only come as links":
+            "Désactivé - les radars arrivent seulement comme liens",
+        "Enabled - veloxes come with their coordinates in {format}":
+            "Activé - les radars arrivent avec leurs coordonnées au format {format}",
+        "Usage: /units {units}\n\nCurrent units: {current}":
+            "Utilisation : /units {units}\n\nUnités actuelles : {current}",
+        "Distances are now shown in {units} units":
+            "Les distances sont maintenant affichées en unités {units}",
+    },
+    "it": {
+        "Checking for updates\n\n": "Controllo aggiornamenti\n\n",
+        "Added:\n": "Aggiunti:\n",
+        "Removed:\n": "Rimossi:\n",
+        "🟢 Removed:\n": "🟢 Rimossi:\n",
+        "Just outside your route ({distance}):\n": "Appena fuori dal tuo percorso ({distance}):\n",
+        "({distance} away)": "(a {distance})",
+        "No changes detected.": "Nessuna modifica rilevata.",
+        "Failed to fetch updates.": "Impossibile recuperare gli aggiornamenti.",
+        "Current List\n\n": "Elenco attuale\n\n",
+        "Recent changes\n\n": "Ultime modifiche\n\n",
+        "No changes recorded yet.": "Nessuna modifica registrata finora.",
+        "🧪 Test message, nothing actually changed.": "🧪 Messaggio di prova, non è cambiato nulla.",
+        "Last updated: {time}": "Ultimo aggiornamento: {time}",
+        "The police website can't be reached, this is the list of {time}.":
+            "Il sito della polizia non è raggiungibile, questo è l'elenco del {time}.",
+        "Update at {time}": "Aggiornamento alle {time}",
+        "today": "oggi",
+        "Monday": "lunedì",
+        "Tuesday": "martedì",
+        "Wednesday": "mercoledì",
+        "Thursday": "giovedì",
+        "Friday": "venerdì",
+        "Saturday": "sabato",
+        "Sunday": "domenica",
+        ", new today": ", nuovo oggi",
+        ", new since {day}": ", nuovo da {day}",
+        "…and {count} more veloxes changed while you were at your hourly limit, "
+        "see /current_list":
+            "…e altri {count} autovelox sono cambiati mentre avevi raggiunto il limite "
+            "orario, vedi /current_list",
+        ", at {coordinates}": ", a {coordinates}",
+        ", followed": ", seguito",
+        " ≈ {distance} from you": " ≈ {distance} da te",
+        ", about {distance} from you": ", a circa {distance} da te",
+        "north": "nord",
+        "south": "sud",
+        "east": "est",
+        "west": "ovest",
+        "meters": "metri",
+        "kilometers": "chilometri",
+        "feet": "piedi",
+        "miles": "miglia",
+        "🔇 mute {velox}": "🔇 silenzia {velox}",
+        "Mute {velox}": "Silenzia {velox}",
+        "Muted {velox}. Use /unmute to undo.": "{velox} silenziato. Usa /unmute per annullare.",
+        "You're subscribed to updates.": "Sei iscritto agli aggiornamenti.",
+        "Already subscribed.": "Già iscritto.",
+        "Tap a setting to toggle it:": "Tocca un'impostazione per attivarla o disattivarla:",
+        "Tap a category to snooze or unsnooze it:":
+            "Tocca una categoria per silenziarla o riattivarla:",
+        "{label} snoozed": "{label} silenziati",
+        "{label} unsnoozed": "{label} riattivati",
+        "Removed veloxes": "Autovelox rimossi",
+        "Veloxes just outside your route": "Autovelox appena fuori dal tuo percorso",
+        "Failed update checks": "Controlli non riusciti",
+        "Notify when nothing changed": "Notifica anche senza modifiche",
+        "Enabled - get status updates even if no changes are detected":
+            "Attivato - ricevi un aggiornamento anche senza modifiche",
+        "Disabled - no status updates if no changes are detected":
+            "Disattivato - nessun aggiornamento senza modifiche",
+        "Strike through removed veloxes": "Barra gli autovelox rimossi",
+        "Enabled - removed veloxes get struck through in previous updates":
+            "Attivato - gli autovelox rimossi vengono barrati nei messaggi precedenti",
+        "Disabled - previous updates are left untouched":
+            "Disattivato - i messaggi precedenti restano invariati",
+        "Merge updates of the same day": "Unisci gli aggiornamenti dello stesso giorno",
+        "Enabled - updates of the same day are merged into a single message":
+            "Attivato - gli aggiornamenti dello stesso giorno vengono uniti in un solo messaggio",
+        "Disabled - every update is sent as a new message":
+            "Disattivato - ogni aggiornamento arriva in un nuovo messaggio",
+        "Also send new veloxes as map pins": "Invia anche i nuovi autovelox come segnaposto",
+        "Enabled - new veloxes also come as map pins you can open in any navigation app":
+            "Attivato - i nuovi autovelox arrivano anche come segnaposto da aprire in "
+            "qualsiasi app di navigazione",
+        "Disabled - new veloxes only come as links":
+            "Disattivato - i nuovi autovelox arrivano solo come link",
+        "Screen reader friendly messages": "Messaggi adatti agli screen reader",
+        "Enabled - updates avoid emoji and spell out coordinates and distances":
+            "Attivato - gli aggiornamenti evitano le emoji e scrivono per esteso "
+            "coordinate e distanze",
+        "Disabled - updates use the compact layout":
+            "Disattivato - gli aggiornamenti usano il formato compatto",
+        "New setting available: {label} (currently {state})":
+            "Nuova impostazione disponibile: {label} (attualmente {state})",
+        "on": "attiva",
+        "off": "disattiva",
+        "Change it in /settings.": "Puoi cambiarla in /settings.",
+        "Choose your language:": "Scegli la tua lingua:",
+        "Language set to {name}.": "Lingua impostata su {name}.",
+        "Failed to fetch the current list, please try again later.":
+            "Impossibile recuperare l'elenco attuale, riprova più tardi.",
+        "none": "non impostato",
+        "You're not subscribed.": "Non sei iscritto.",
+        "Please subscribe with /start first.": "Iscriviti prima con /start.",
+        "Too many commands, please try again in {minutes} minutes.":
+            "Troppi comandi, riprova tra {minutes} minuti.",
+        "Velox map": "Mappa degli autovelox",
+        "An update check is already running, please try again in a moment.":
+            "È già in corso un controllo degli aggiornamenti, riprova tra un attimo.",
+        "Speed camera": "Autovelox",
+        ", {distance} away": ", a {distance}",
+        "🧭 Navigate": "🧭 Naviga",
+        "This velox is no longer listed.": "Questo autovelox non è più in elenco.",
+        "Disabled - updates are sent as new messages again":
+            "Disattivato - gli aggiornamenti arrivano di nuovo come nuovi messaggi",
+        "Enabled - the pinned list is edited on every change":
+            "Attivato - l'elenco fissato viene modificato a ogni cambiamento",
+        "Could not pin the list. Make sure I'm allowed to pin messages.":
+            "Impossibile fissare l'elenco. Assicurati che io possa fissare i messaggi.",
+        "Usage: /follow &lt;velox name&gt;\n\n": "Uso: /follow &lt;nome dell'autovelox&gt;\n\n",
+        "Followed veloxes:\n": "Autovelox seguiti:\n",
+        "You're not following any velox.": "Non segui nessun autovelox.",
+        "No velox matching '{query}' in the current list.":
+            "Nessun autovelox corrisponde a '{query}' nell'elenco attuale.",
+        "Multiple veloxes match, please be more specific:\n":
+            "Più autovelox corrispondono, sii più preciso:\n",
+        "Already following {velox}.": "Segui già {velox}.",
+        "Following {velox} - it will be marked with ⭐ in updates.":
+            "Ora segui {velox} - sarà segnato con ⭐ negli aggiornamenti.",
+        "Usage: /unfollow &lt;followed velox name&gt;":
+            "Uso: /unfollow &lt;nome di un autovelox seguito&gt;",
+        "Multiple followed veloxes match, please be more specific:\n":
+            "Più autovelox seguiti corrispondono, sii più preciso:\n",
+        "No longer following {velox}.": "Non segui più {velox}.",
+        "Usage: /unmute &lt;muted velox name&gt;\n\n":
+            "Uso: /unmute &lt;nome di un autovelox silenziato&gt;\n\n",
+        "Muted veloxes:\n": "Autovelox silenziati:\n",
+        "You haven't muted any velox.": "Non hai silenziato nessun autovelox.",
+        "Multiple muted veloxes match, please be more specific:\n":
+            "Più autovelox silenziati corrispondono, sii più preciso:\n",
+        "Unmuted {velox}.": "{velox} non è più silenziato.",
+        "No veloxes within {distance} of this location.":
+            "Nessun autovelox entro {distance} da questo luogo.",
+        "Veloxes within {distance}\n\n": "Autovelox entro {distance}\n\n",
+        "/nearest lists the veloxes closest to your home location, save it with /set_home first. "
+        "Or share any location to see the veloxes around it.":
+            "/nearest elenca gli autovelox più vicini a casa tua, salvala prima con /set_home. "
+            "Oppure condividi un luogo qualsiasi per vedere gli autovelox nei dintorni.",
+        "Usage: /nearest [count] [map]\ne.g. /nearest 5 map also sends the 5 veloxes as map pins":
+            "Uso: /nearest [numero] [map]\nad es. /nearest 5 map invia anche i 5 autovelox come "
+            "segnaposto",
+        "No veloxes with known coordinates right now.":
+            "Al momento nessun autovelox con coordinate note.",
+        "Closest veloxes to your home\n\n": "Autovelox più vicini a casa tua\n\n",
+        "Couldn't find any track in this GPX file.": "Nessun tracciato trovato in questo file GPX.",
+        "Route saved - you'll only be notified about veloxes within {distance} of it.\nUse "
+        "/route_distance to change the distance and /clear_route to remove the route.":
+            "Percorso salvato - riceverai solo gli autovelox entro {distance} da esso.\nUsa "
+            "/route_distance per cambiare la distanza e /clear_route per rimuovere il percorso.",
+        "Usage: /commute <home lat,long> <work lat,long>\ne.g. /commute 47.0502,8.3093 "
+        "47.0165,8.3149":
+            "Uso: /commute <casa lat,long> <lavoro lat,long>\nad es. /commute 47.0502,8.3093 "
+            "47.0165,8.3149",
+        "Couldn't compute a route between these points.":
+            "Impossibile calcolare un percorso tra questi punti.",
+        "Commute saved - you'll only be notified about veloxes within {distance} of it.\nUse "
+        "/route_distance to change the distance and /clear_route to remove the route.":
+            "Tragitto salvato - riceverai solo gli autovelox entro {distance} da esso.\nUsa "
+            "/route_distance per cambiare la distanza e /clear_route per rimuovere il percorso.",
+        "Usage: /route_distance <meters>\n\nCurrent distance: {distance}":
+            "Uso: /route_distance <metri>\n\nDistanza attuale: {distance}",
+        "Veloxes within {distance} of your route will be notified.":
+            "Gli autovelox entro {distance} dal tuo percorso verranno notificati.",
+        "Usage: /soft_distance <meters>\nMust be more than your route distance ({distance}), 0 "
+        "disables it.\n\nCurrent soft distance: {current}":
+            "Uso: /soft_distance <metri>\nDeve superare la distanza del percorso ({distance}), 0 "
+            "la disattiva.\n\nDistanza morbida attuale: {current}",
+        "Disabled - veloxes outside your route are never notified":
+            "Disattivato - gli autovelox fuori dal tuo percorso non vengono mai notificati",
+        "Enabled - once a day you'll hear about veloxes up to {distance} from your route":
+            "Attivato - una volta al giorno saprai degli autovelox fino a {distance} dal tuo "
+            "percorso",
+        "No route saved. Send me a GPX file or use /commute to set one.":
+            "Nessun percorso salvato. Inviami un file GPX o usa /commute per impostarne uno.",
+        "Route removed - you'll be notified about all veloxes again.":
+            "Percorso rimosso - riceverai di nuovo tutti gli autovelox.",
+        "Home location saved. Use /set_radius to only get veloxes around it.":
+            "Casa salvata. Usa /set_radius per ricevere solo gli autovelox nei dintorni.",
+        "Home location saved - you'll only be notified about veloxes within {distance} of it.":
+            "Casa salvata - riceverai solo gli autovelox entro {distance} da essa.",
+        "Usage: /set_home [lat,long]\ne.g. /set_home 47.0502,8.3093":
+            "Uso: /set_home [lat,long]\nad es. /set_home 47.0502,8.3093",
+        "Share your home location (📎 > Location) and I'll remember it.":
+            "Condividi la posizione di casa tua (📎 > Posizione) e me la ricorderò.",
+        "Usage: /set_radius <km>\n0 disables the filter.\n\nCurrent radius: {current}":
+            "Uso: /set_radius <km>\n0 disattiva il filtro.\n\nRaggio attuale: {current}",
+        "Disabled - veloxes are notified wherever they are":
+            "Disattivato - gli autovelox vengono notificati ovunque si trovino",
+        "Radius set to {distance}. Use /set_home to tell me where home is.":
+            "Raggio impostato a {distance}. Usa /set_home per dirmi dov'è casa tua.",
+        "Enabled - only veloxes within {distance} of home are notified":
+            "Attivato - vengono notificati solo gli autovelox entro {distance} da casa",
+        "Usage: /filter_area &lt;municipality&gt; adds or removes a municipality, /filter_area "
+        "all removes the filter\n\n":
+            "Uso: /filter_area &lt;comune&gt; aggiunge o rimuove un comune, /filter_area all "
+            "rimuove il filtro\n\n",
+        "Only veloxes in these municipalities are notified:\n":
+            "Vengono notificati solo gli autovelox di questi comuni:\n",
+        "Veloxes of every municipality are notified.":
+            "Vengono notificati gli autovelox di tutti i comuni.",
+        "Veloxes of every municipality will be notified.":
+            "Verranno notificati gli autovelox di tutti i comuni.",
+        "Removed {area} from your municipalities.": "{area} rimosso dai tuoi comuni.",
+        "Added {area} to your municipalities.": "{area} aggiunto ai tuoi comuni.",
+        "There is currently no velox listed there.":
+            "Al momento non vi è elencato nessun autovelox.",
+        "Usage: /max_per_hour <messages>\n0 removes the limit.\n\nCurrent limit: {current}":
+            "Uso: /max_per_hour <messaggi>\n0 rimuove il limite.\n\nLimite attuale: {current}",
+        "Disabled - you'll get every update as it comes":
+            "Disattivato - riceverai ogni aggiornamento appena arriva",
+        "Enabled - at most {cap} updates per hour, the rest is summarized in a single message":
+            "Attivato - al massimo {cap} aggiornamenti all'ora, il resto viene riassunto in un "
+            "solo messaggio",
+        "Usage: /no_updates_every <hours>\n0 sends one after every check, 24 about once a day. "
+        "Only applies with /notify_no_updates enabled.\n\nCurrent interval: {current}":
+            "Uso: /no_updates_every <ore>\n0 ne invia uno dopo ogni controllo, 24 circa una volta "
+            "al giorno. Vale solo con /notify_no_updates attivo.\n\nIntervallo attuale: {current}",
+        "{hours} hours": "{hours} ore",
+        "every check": "ogni controllo",
+        "You'll get a \"no changes\" message after every check":
+            "Riceverai un messaggio \"nessuna modifica\" dopo ogni controllo",
+        "You'll get at most one \"no changes\" message every {hours} hours":
+            "Riceverai al massimo un messaggio \"nessuna modifica\" ogni {hours} ore",
+        "Usage: /timezone <name>\ne.g. /timezone Europe/Zurich, times in your messages are then "
+        "shown in that timezone.\n\nCurrent timezone: {current}":
+            "Uso: /timezone <nome>\nad es. /timezone Europe/Zurich, gli orari nei tuoi messaggi "
+            "vengono poi mostrati in quel fuso orario.\n\nFuso orario attuale: {current}",
+        "the bot's server time": "l'ora del server del bot",
+        "Times are now shown in {timezone}, currently {time}":
+            "Gli orari ora sono mostrati in {timezone}, attualmente {time}",
+        "Usage: /coordinates <format>|off\n": "Uso: /coordinates <formato>|off\n",
+        "decimal degrees, e.g. 47.05020, 8.30930": "gradi decimali, ad es. 47.05020, 8.30930",
+        "degrees, minutes and seconds, e.g. 47°03'00.7\"N 8°18'33.5\"E":
+            "gradi, minuti e secondi, ad es. 47°03'00.7\"N 8°18'33.5\"E",
+        "Swiss LV95, e.g. 2'666'157, 1'211'386": "LV95 svizzero, ad es. 2'666'157, 1'211'386",
+        "Current format: {current}": "Formato attuale: {current}",
+        "Disabled - veloxes only come as links":
+            "Disattivato - gli autovelox arrivano solo come link",
+        "Enabled - veloxes come with their coordinates in {format}":
+            "Attivato - gli autovelox arrivano con le coordinate in formato {format}",
+        "Usage: /units {units}\n\nCurrent units: {current}":
+            "Uso: /units {units}\n\nUnità attuali: {current}",
+        "Distances are now shown in {units} units":
+            "Le distanze ora sono mostrate in unità {units}",
+    },
+}
+
+
+def translate(language, text, **kwargs):
+    """The text in the given language, English if not translated, formatted with kwargs"""
+    text = TRANSLATIONS.get(language, {}).get(text, text)
+    return text.format(**kwargs) if kwargs else text
@@ -0,0 +1,97 @@
+"""Logging setup, with spans tagging the records of a check, a broadcast or an update"""
+
+import contextvars
+import functools
+import json
+import logging
+import sys
+from contextlib import contextmanager
+from datetime import datetime
+
+# fields of the innermost span, e.g. {"span": "check", "trigger": "manual"}
+current_span = contextvars.ContextVar("current_span", default={})
+
+
+@contextmanager
+def span(name, **fields):
+    """Tag the records logged inside the block, nested spans add their fields"""
+    token = current_span.set({**current_span.get(), "span": name, **fields})
+    try:
+        yield
+    finally:
+        current_span.reset(token)
+
+
+def traced(name):
+    """Decorator running a coroutine function inside a span"""
+    def decorator(func):
+        @functools.wraps(func)
+        async def wrapper(*args, **kwargs):
+            with span(name):
+                return await func(*args, **kwargs)
+        return wrapper
+    return decorator
+
+
+def enter_span(name, **fields):
+    """Tag the records logged from now on in the current context, e.g. by later handlers"""
+    current_span.set({"span": name, **fields})
+
+
+class SpanFilter(logging.Filter):
+    def filter(self, record):
+        record.span = current_span.get()
+        return True
+
+
+class TextFormatter(logging.Formatter):
+    def format(self, record):
+        line = super().format(record)
+        if record.span:
+            fields = " ".join(f"{k}={v}" for k, v in record.span.items() if k != "span")
+            line += f" [{record.span['span']}{' ' + fields if fields else ''}]"
+        return line
+
+
+class JsonFormatter(logging.Formatter):
+    """One JSON object per line, for log aggregation"""
+    def format(self, record):
+        entry = {"time": datetime.fromtimestamp(record.created).isoformat(timespec='milliseconds'),
+                 "level": record.levelname, "logger": record.name,
+                 "message": record.getMessage(), **record.span}
+        if record.exc_info:
+            entry["exception"] = self.formatException(record.exc_info)
+        return json.dumps(entry, ensure_ascii=False)
+
+
+def setup_logging(json_output=False, level="INFO"):
+    """Log to stderr from level on, a name like DEBUG or WARNING"""
+    handler = logging.StreamHandler(sys.stderr)
+    handler.addFilter(SpanFilter())
+    handler.setFormatter(JsonFormatter() if json_output else
+                         TextFormatter("%(asctime)s %(levelname)s %(name)s: %(message)s"))
+    name = str(level).upper()
+    known = isinstance(logging.getLevelName(name), int)
+    logging.basicConfig(level=name if known else logging.INFO, handlers=[handler])
+    if not known:
+        logging.getLogger("velox.state").warning(f"Unknown log level {level!r}, using INFO")
+
+    # the HTTP client logs every single polling request
+    logging.getLogger("httpx").setLevel(logging.WARNING)
+
+
+def setup_error_reporting(dsn):
+    """Report logged errors and uncaught exceptions to Sentry, tagged with the current span"""
+    try:
+        import sentry_sdk
+    except ImportError:
+        logging.getLogger("velox.state").warning("SENTRY_DSN is set but sentry-sdk isn't installed")
+        return
+
+    def before_send(event, _hint):
+        event.setdefault("tags", {}).update({k: str(v) for k, v in current_span.get().items()})
+        return event
+
+    # the default logging integration turns every ERROR record into an event. Frame locals
+    # would carry the bot token, chat ids and message texts, so they're never sent
+    sentry_sdk.init(dsn=dsn, before_send=before_send, include_local_variables=False)
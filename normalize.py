@@ -0,0 +1,33 @@
+"""Normalization of the velox names scraped from the police website"""
+
+import re
+import unicodedata
+
+# hyphens, en/em dashes and minus signs all end up as a plain "-"
+DASHES_RE = re.compile(r"[\u2010-\u2015\u2212]")
+
+
+def normalize_velox_name(name):
+    """Return the name with consistent unicode, whitespace, dashes and parentheses"""
+    name = unicodedata.normalize('NFC', name)
+    name = DASHES_RE.sub('-', name)
+
+    # any kind of whitespace (nbsp, tabs, newlines...) becomes a single space
+    name = re.sub(r"\s+", " ", name)
+
+    # "( Richtung Kriens )" -> "(Richtung Kriens)", "Strasse(" -> "Strasse ("
+    name = re.sub(r"\(\s+", "(", name)
+    name = re.sub(r"\s+\)", ")", name)
+    name = re.sub(r"(?<=\S)\(", " (", name)
+
+    return name.strip()
+
+
+def collation_key(name):
+    """Sort key ordering names like a dictionary does, e.g. "Ämmen" before "Zug"
+
+    Accents and case only matter between names that are otherwise equal.
+    """
+    decomposed = unicodedata.normalize('NFKD', name)
+    base = "".join(c for c in decomposed if not unicodedata.combining(c))
+    return base.casefold(), name.casefold(), name
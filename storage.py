@@ -0,0 +1,27 @@
+"""Crash safe writes of the state files"""
+
+import os
+import shutil
+from contextlib import contextmanager
+
+
+@contextmanager
+def atomic_open(path, backup=True):
+    """Like open(path, 'w'), but the file is only replaced once everything is written
+
+    A crash mid-write leaves the previous version in place. With backup, the
+    previous version is also kept as path.bak.
+    """
+    tmp = f"{path}.tmp"
+    try:
+        with open(tmp, 'w', encoding='utf-8') as f:
+            yield f
+            f.flush()
+            os.fsync(f.fileno())
+    except BaseException:
+        os.remove(tmp)
+        raise
+
+    if backup and os.path.exists(path):
+        shutil.copy2(path, f"{path}.bak")
+    os.replace(tmp, path)
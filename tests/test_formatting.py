@@ -0,0 +1,80 @@
+import unittest
+
+from formatting import dms_coordinates, escape, link, strike, strike_list_item
+
+
+class EscapeTest(unittest.TestCase):
+    def test_html_characters(self):
+        self.assertEqual(escape("A&B <Kreisel>"), "A&amp;B &lt;Kreisel&gt;")
+
+    def test_quotes_are_kept(self):
+        self.assertEqual(escape("Rank 'Luzernerhof'"), "Rank 'Luzernerhof'")
+
+    def test_non_strings(self):
+        self.assertEqual(escape(42), "42")
+
+
+class LinkTest(unittest.TestCase):
+    def test_escapes_the_text(self):
+        self.assertEqual(link("https://maps.example/?q=1", "A&B <x>"),
+                         "<a href='https://maps.example/?q=1'>A&amp;B &lt;x&gt;</a>")
+
+    def test_quote_in_url_does_not_close_the_attribute(self):
+        self.assertEqual(link("https://maps.example/?q=St. Karli's", "x"),
+                         "<a href='https://maps.example/?q=St. Karli&#x27;s'>x</a>")
+
+
+class StrikeTest(unittest.TestCase):
+    def test_strike(self):
+        self.assertEqual(strike("<a href='u'>x</a>"), "<s><a href='u'>x</a></s>")
+
+
+class StrikeListItemTest(unittest.TestCase):
+    def message(self, *names):
+        return "Added:\n" + "".join(f"- {link(f'https://maps.example/{i}', name)}\n"
+                                    for i, name in enumerate(names))
+
+    def test_strikes_only_the_given_velox(self):
+        msg = self.message("Luzern, Haldenstrasse", "Emmen, Seetalstrasse")
+        self.assertEqual(strike_list_item(msg, "Emmen, Seetalstrasse"),
+                         "Added:\n"
+                         "- <a href='https://maps.example/0'>Luzern, Haldenstrasse</a>\n"
+                         "- <s><a href='https://maps.example/1'>Emmen, Seetalstrasse</a></s>\n")
+
+    def test_names_with_html_characters(self):
+        for name in ("Kriens <Obernau>", "A2 Ausfahrt Luzern-Zentrum & Kasernenplatz",
+                     "Rank 'Luzernerhof'"):
+            msg = self.message(name)
+            self.assertEqual(strike_list_item(msg, name),
+                             f"Added:\n- {strike(link('https://maps.example/0', name))}\n")
+
+    def test_regex_characters_in_the_name(self):
+        msg = self.message("Horw (Richtung Kriens)", "Horw (Richtung Kriens)+")
+        self.assertEqual(strike_list_item(msg, "Horw (Richtung Kriens)+"),
+                         f"Added:\n- {link('https://maps.example/0', 'Horw (Richtung Kriens)')}\n"
+                         f"- {strike(link('https://maps.example/1', 'Horw (Richtung Kriens)+'))}\n")
+
+    def test_prefix_of_another_name_is_not_struck(self):
+        msg = self.message("Luzern, Seeburgstrasse")
+        self.assertEqual(strike_list_item(msg, "Luzern, Seeburg"), msg)
+
+    def test_unknown_name(self):
+        msg = self.message("Luzern, Haldenstrasse")
+        self.assertEqual(strike_list_item(msg, "Emmen"), msg)
+
+
+class DmsCoordinatesTest(unittest.TestCase):
+    def test_luzern(self):
+        self.assertEqual(dms_coordinates(("47.05019", "8.30931")), "47°03'00.7\"N 8°18'33.5\"E")
+
+    def test_seconds_rounding_up_carry_over(self):
+        self.assertEqual(dms_coordinates((47 + 59 / 60 + 59.9999 / 3600, 8 + 59.96 / 3600)),
+                         "48°00'00.0\"N 8°01'00.0\"E")
+
+    def test_south_and_west(self):
+        self.assertEqual(dms_coordinates((-33.8568, -151.2153)),
+                         "33°51'24.5\"S 151°12'55.1\"W")
+
+
+if __name__ == "__main__":
+    unittest.main()
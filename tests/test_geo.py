@@ -0,0 +1,70 @@
+import unittest
+
+from geo import (haversine_distance, parse_gpx, route_distance, segment_distance,
+                 simplify_route, wgs84_to_lv95)
+
+# one degree along a meridian, 2 * pi * EARTH_RADIUS_M / 360
+DEGREE_M = 111194.93
+
+
+class HaversineDistanceTest(unittest.TestCase):
+    def test_one_degree_of_latitude(self):
+        self.assertAlmostEqual(haversine_distance((47, 8), (48, 8)), DEGREE_M, places=1)
+
+    def test_one_degree_of_longitude_at_the_equator(self):
+        self.assertAlmostEqual(haversine_distance((0, 0), (0, 1)), DEGREE_M, places=1)
+
+    def test_strings_from_the_page(self):
+        self.assertEqual(haversine_distance(("47.0502", "8.3093"), ("47.0502", "8.3093")), 0)
+
+
+class Wgs84ToLv95Test(unittest.TestCase):
+    def test_swisstopo_example(self):
+        # the example of the swisstopo approximate formulas
+        east, north = wgs84_to_lv95((46 + 2 / 60 + 38.87 / 3600, 8 + 43 / 60 + 49.79 / 3600))
+        self.assertAlmostEqual(east, 2700000, delta=1)
+        self.assertAlmostEqual(north, 1100000, delta=1)
+
+
+class RouteDistanceTest(unittest.TestCase):
+    def test_point_beside_a_segment(self):
+        self.assertAlmostEqual(segment_distance((47.001, 8), (47, 7.99), (47, 8.01)),
+                               DEGREE_M / 1000, places=1)
+
+    def test_point_past_the_end_of_a_segment(self):
+        self.assertAlmostEqual(segment_distance((47, 8.02), (47, 7.99), (47, 8.01)),
+                               haversine_distance((47, 8.02), (47, 8.01)), delta=1)
+
+    def test_closest_segment(self):
+        route = [(47, 7.99), (47, 8.01), (47.1, 8.01)]
+        self.assertAlmostEqual(route_distance((47.05, 8.011), route),
+                               haversine_distance((47.05, 8.011), (47.05, 8.01)), delta=1)
+
+    def test_single_point_route(self):
+        self.assertAlmostEqual(route_distance((47.001, 8), [(47, 8)]), DEGREE_M / 1000, places=1)
+
+
+class SimplifyRouteTest(unittest.TestCase):
+    def test_drops_close_points_and_keeps_the_ends(self):
+        route = [(47, 8), (47.0001, 8), (47.002, 8), (47.0021, 8)]
+        self.assertEqual(simplify_route(route), [(47, 8), (47.002, 8), (47.0021, 8)])
+
+    def test_short_routes(self):
+        self.assertEqual(simplify_route([(47, 8), (47, 8)]), [(47, 8), (47, 8)])
+
+
+class ParseGpxTest(unittest.TestCase):
+    def test_track_points(self):
+        gpx = (b'<gpx xmlns="http://www.topografix.com/GPX/1/1"><trk><trkseg>'
+               b'<trkpt lat="47.05" lon="8.30"/><trkpt lat="47.01" lon="8.31"/>'
+               b'</trkseg></trk></gpx>')
+        self.assertEqual(parse_gpx(gpx), [(47.05, 8.30), (47.01, 8.31)])
+
+    def test_route_points_without_a_track(self):
+        gpx = (b'<gpx xmlns="http://www.topografix.com/GPX/1/0"><rte>'
+               b'<rtept lat="47.05" lon="8.30"/></rte></gpx>')
+        self.assertEqual(parse_gpx(gpx), [(47.05, 8.30)])
+
+
+if __name__ == "__main__":
+    unittest.main()
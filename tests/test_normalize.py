@@ -0,0 +1,48 @@
+import unittest
+
+from normalize import collation_key, normalize_velox_name
+
+
+class NormalizeVeloxNameTest(unittest.TestCase):
+    def test_dashes(self):
+        for dash in "\u2010\u2011\u2012\u2013\u2014\u2015\u2212":
+            self.assertEqual(normalize_velox_name(f"Luzern {dash} Kriens"), "Luzern - Kriens")
+
+    def test_whitespace(self):
+        self.assertEqual(normalize_velox_name("Luzern,\u00a0Haldenstrasse"),
+                         "Luzern, Haldenstrasse")
+        self.assertEqual(normalize_velox_name("  Emmen,\t\nSeetalstrasse  "),
+                         "Emmen, Seetalstrasse")
+
+    def test_parentheses(self):
+        self.assertEqual(normalize_velox_name("Kriens, Luzernerstrasse( Richtung Luzern )"),
+                         "Kriens, Luzernerstrasse (Richtung Luzern)")
+        self.assertEqual(normalize_velox_name("Horw, Kantonsstrasse (Richtung Hergiswil)"),
+                         "Horw, Kantonsstrasse (Richtung Hergiswil)")
+
+    def test_combining_characters(self):
+        self.assertEqual(normalize_velox_name("Ebikon, Zu\u0308richstrasse"),
+                         "Ebikon, Zürichstrasse")
+
+    def test_idempotent(self):
+        name = normalize_velox_name("Luzern – Kriens ( Richtung Horw )")
+        self.assertEqual(normalize_velox_name(name), name)
+
+
+class CollationKeyTest(unittest.TestCase):
+    def test_accents_sort_with_their_base_letter(self):
+        names = ["Zug", "Ämmen", "Adligenswil", "Ebikon"]
+        self.assertEqual(sorted(names, key=collation_key),
+                         ["Adligenswil", "Ämmen", "Ebikon", "Zug"])
+
+    def test_case_insensitive(self):
+        self.assertEqual(sorted(["emmen", "Dierikon", "Ebikon"], key=collation_key),
+                         ["Dierikon", "Ebikon", "emmen"])
+
+    def test_ties_broken_by_accents_and_case(self):
+        self.assertLess(collation_key("Emmen"), collation_key("Émmen"))
+        self.assertNotEqual(collation_key("emmen"), collation_key("Emmen"))
+
+
+if __name__ == "__main__":
+    unittest.main()
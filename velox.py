@@ -2,296 +2,4135 @@
 
 import argparse
 import asyncio
+import hashlib
 import json
+import logging
 import os
+import random
 import re
+import secrets
+import shutil
 import sys
+import threading
+import time
+import xml.etree.ElementTree as ET
+from dataclasses import dataclass
+from datetime import date, datetime, timedelta
+from enum import IntFlag
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
 
 import requests
 from apscheduler.schedulers.asyncio import AsyncIOScheduler
 from apscheduler.triggers.cron import CronTrigger
 from bs4 import BeautifulSoup
-from telegram import Update
-from telegram.constants import ParseMode
-from telegram.ext import ApplicationBuilder, CommandHandler, ContextTypes
+from telegram import (InlineKeyboardButton, InlineKeyboardMarkup, InlineQueryResultVenue,
+                      Update)
+from telegram.constants import MessageLimit, ParseMode
+from telegram.error import BadRequest, Forbidden, NetworkError, RetryAfter, TelegramError
+from telegram.ext import (AIORateLimiter, ApplicationBuilder, ApplicationHandlerStop,
+                          CallbackQueryHandler, CommandHandler, ContextTypes, InlineQueryHandler,
+                          MessageHandler, TypeHandler, filters)
+
+import export
+import geo
+from formatting import (bullet_list, dms_coordinates, escape, italic, link, spoken_coordinates,
+                        strike_list_item, swiss_coordinates)
+from http_server import start_http_server
+from i18n import DEFAULT_LANGUAGE, LANGUAGES, WEEKDAYS, translate
+from logs import enter_span, setup_error_reporting, setup_logging, span, traced
+from normalize import collation_key, normalize_velox_name
+from storage import atomic_open
+
+scrape_log = logging.getLogger("velox.scrape")
+notify_log = logging.getLogger("velox.notify")
+command_log = logging.getLogger("velox.commands")
+state_log = logging.getLogger("velox.state")
 
 BASE_DIR = os.path.abspath(os.path.dirname(__file__))
 
+# where config and state files live, changed by --profile
+STATE_DIR = BASE_DIR
+
+# local HTML file used instead of the police website, set by --offline
+OFFLINE_FIXTURE = None
+
+VELOX_LIST_URL = ('https://polizei.lu.ch/organisation/sicherheit_verkehrspolizei/verkehrspolizei/'
+                  'spezialversorgung/verkehrssicherheit/Aktuelle_Tempomessungen')
+OSRM_URL = 'https://router.project-osrm.org'
+# looks up veloxes the page lists without coordinates
+GEOCODE_URL = 'https://nominatim.openstreetmap.org/search'
+# Nominatim's usage policy allows at most one request per second
+GEOCODE_MIN_INTERVAL_S = 1
+HTTP_CONNECT_TIMEOUT_S = 5
+HTTP_TIMEOUT_S = 30
+
+# failed page fetches and Telegram sends are retried with exponential backoff and jitter
+RETRY_ATTEMPTS = 3
+RETRY_BASE_DELAY_S = 1
+RETRY_MAX_DELAY_S = 30
+
+# after this many failed checks in a row the website is only probed every CIRCUIT_PROBE_S
+CIRCUIT_MAX_FAILURES = 5
+CIRCUIT_PROBE_S = 1800
+
+# max number of update messages remembered per chat for later edits
+MAX_TRACKED_MESSAGES = 50
+
+UPDATE_HEADER = "Checking for updates\n\n"
+
+# default max distance between a velox and a user's route
+DEFAULT_ROUTE_DISTANCE_M = 500
+
+# radius of the veloxes listed around a shared location
+NEARBY_DISTANCE_M = 5000
+
+# veloxes listed by /nearest by default, and at most
+NEAREST_DEFAULT_COUNT = 3
+NEAREST_MAX_COUNT = 10
+
+# max number of results of an inline query
+MAX_INLINE_RESULTS = 20
+
+# more than FLOOD_MAX_COMMANDS within FLOOD_WINDOW_S seconds get a chat throttled,
+# for THROTTLE_S seconds the first time and twice as long at every repetition
+FLOOD_MAX_COMMANDS = 10
+FLOOD_WINDOW_S = 60
+THROTTLE_S = 600
+
+# broadcasts to at least BROADCAST_PROGRESS_MIN_CHATS chats report their progress
+# to the admins, refreshed every BROADCAST_PROGRESS_EVERY chats
+BROADCAST_PROGRESS_MIN_CHATS = 50
+BROADCAST_PROGRESS_EVERY = 10
+# passed to the rate limiter of the bot, which python-telegram-bot[rate-limiter] provides
+BROADCAST_MAX_PER_S = 25
+
+# max time the shutdown waits for a cancelled broadcast to stop
+SHUTDOWN_TIMEOUT_S = 10
+
+# update deliveries waiting for the delivery worker before checks wait for it in turn
+DELIVERY_QUEUE_SIZE = 10
+
+# consecutive failed updates to a chat after which the failure is reported as an error
+REPORTED_SEND_FAILURES = 3
+
+# number of entries shown by /admin_audit and /history by default
+AUDIT_DEFAULT_ENTRIES = 20
+HISTORY_DEFAULT_ENTRIES = 10
+# more changes than this wouldn't fit in a single message
+HISTORY_MAX_ENTRIES = 50
+
+DEFAULT_CHECK_INTERVAL_MINUTES = 60
+
+# /healthz fails when no check succeeded in this many check intervals
+HEALTH_MAX_MISSED_CHECKS = 3
+
+# polling is restarted after this long without any update, harmless if the bot was just idle
+DISPATCHER_SILENCE_S = 6 * 3600
+
+# default retention of snapshots and of update messages tracked for edits
+DEFAULT_SNAPSHOT_RETENTION_DAYS = 90
+DEFAULT_SENT_MESSAGES_RETENTION_WEEKS = 4
+# removed veloxes and changelog entries are forgotten after this long, audit entries too
+DEFAULT_HISTORY_RETENTION_MONTHS = 24
+DEFAULT_AUDIT_RETENTION_MONTHS = 12
+
+# daily backups of these state files are kept in backups/, the most recent ones only
+BACKUP_FILES = ("chat_ids.json", "previous_dict.json", "velox_history.json", "denylist.json",
+                "changes.jsonl", "changes_purged", "audit.jsonl", "config.json")
+BACKUP_RETENTION = 14
+
+# constants above that deployments can tune without touching the code, from
+# config.json or from VELOX_<NAME> environment variables (which win). The check
+# interval is one of the EDITABLE_CONFIGS instead, e.g. VELOX_CHECK_INTERVAL_MINUTES
+TUNABLE_CONSTANTS = (
+    "VELOX_LIST_URL", "OSRM_URL", "GEOCODE_URL", "HTTP_CONNECT_TIMEOUT_S", "HTTP_TIMEOUT_S",
+    "RETRY_ATTEMPTS", "RETRY_BASE_DELAY_S", "RETRY_MAX_DELAY_S",
+    "CIRCUIT_MAX_FAILURES", "CIRCUIT_PROBE_S", "MAX_TRACKED_MESSAGES",
+    "DEFAULT_ROUTE_DISTANCE_M", "NEARBY_DISTANCE_M", "NEAREST_DEFAULT_COUNT",
+    "MAX_INLINE_RESULTS",
+    "FLOOD_MAX_COMMANDS", "FLOOD_WINDOW_S", "THROTTLE_S",
+    "BROADCAST_PROGRESS_MIN_CHATS", "BROADCAST_PROGRESS_EVERY", "BROADCAST_MAX_PER_S",
+    "SHUTDOWN_TIMEOUT_S", "DELIVERY_QUEUE_SIZE", "REPORTED_SEND_FAILURES", "AUDIT_DEFAULT_ENTRIES",
+    "HISTORY_DEFAULT_ENTRIES", "HEALTH_MAX_MISSED_CHECKS",
+    "DEFAULT_SNAPSHOT_RETENTION_DAYS", "DEFAULT_SENT_MESSAGES_RETENTION_WEEKS",
+    "DEFAULT_HISTORY_RETENTION_MONTHS", "DEFAULT_AUDIT_RETENTION_MONTHS",
+    "BACKUP_RETENTION", "DISPATCHER_SILENCE_S",
+)
+
+
+class Category(IntFlag):
+    """Notification categories that can be snoozed, stored as a bit set"""
+    REMOVALS = 1
+    NEAR_MISSES = 2
+    FAILURES = 4
+
+
+CATEGORY_LABELS = {
+    Category.REMOVALS: "Removed veloxes",
+    Category.NEAR_MISSES: "Veloxes just outside your route",
+    Category.FAILURES: "Failed update checks",
+}
+
+
+@dataclass
+class Preference:
+    """A per-chat setting, boolean ones with a label can be toggled by users"""
+    default: object
+    label: str = None
+    command: str = None
+    enabled_msg: str = None
+    disabled_msg: str = None
+    # PREFERENCES_VERSION that introduced it, existing chats get told about newer toggles
+    version: int = 1
+
+
+PREFERENCES = {
+    "notify_for_no_updates": Preference(
+        False, "Notify when nothing changed", "notify_no_updates",
+        "Enabled - get status updates even if no changes are detected",
+        "Disabled - no status updates if no changes are detected"),
+    "strike_removed": Preference(
+        False, "Strike through removed veloxes", "strike_removed",
+        "Enabled - removed veloxes get struck through in previous updates",
+        "Disabled - previous updates are left untouched", version=2),
+    "merge_updates": Preference(
+        False, "Merge updates of the same day", "merge_updates",
+        "Enabled - updates of the same day are merged into a single message",
+        "Disabled - every update is sent as a new message", version=2),
+    "send_venues": Preference(
+        False, "Also send new veloxes as map pins", "venues",
+        "Enabled - new veloxes also come as map pins you can open in any navigation app",
+        "Disabled - new veloxes only come as links", version=2),
+    "accessible": Preference(
+        False, "Screen reader friendly messages", "accessibility",
+        "Enabled - updates avoid emoji and spell out coordinates and distances",
+        "Disabled - updates use the compact layout", version=2),
+    # max update messages per hour, 0 means no limit
+    "max_per_hour": Preference(0),
+    # hours between two "no changes" messages, 0 sends one after every check
+    "no_updates_every_h": Preference(0),
+    "route_distance": Preference(DEFAULT_ROUTE_DISTANCE_M),
+    # meters around the home location, 0 disables the filter
+    "home_radius": Preference(0),
+    "soft_route_distance": Preference(0),
+    "snoozed": Preference(0),
+    # chats that subscribed before translations existed keep getting English
+    "language": Preference("en"),
+    # IANA name like Europe/Zurich, empty uses the server's timezone
+    "timezone": Preference(""),
+    # one of COORDINATE_FORMATS, empty doesn't show coordinates next to the links
+    "coordinate_format": Preference(""),
+    "distance_unit": Preference("metric"),
+}
+
+# bump by giving new preferences a higher version
+PREFERENCES_VERSION = max(pref.version for pref in PREFERENCES.values())
+
+
+def get_pref(chat, key):
+    return chat.get("preferences", {}).get(key, PREFERENCES[key].default)
+
+
+def set_pref(chat, key, value):
+    if not isinstance(value, type(PREFERENCES[key].default)):
+        raise TypeError(f"{key} must be a {type(PREFERENCES[key].default).__name__}")
+    chat.setdefault("preferences", {})[key] = value
+
+
+def user_language(user):
+    """The Telegram user's language if there's a translation for it, the default otherwise"""
+    if user and user.language_code and user.language_code[:2] in LANGUAGES:
+        return user.language_code[:2]
+    return DEFAULT_LANGUAGE
+
+
+def tr(chat, text, **kwargs):
+    """Translate text to the chat's language, English if there's no chat (e.g. logs)"""
+    return translate(get_pref(chat, "language") if chat else "en", text, **kwargs)
+
+
+def to_chat_time(dt, chat=None):
+    """A naive server-local datetime converted to the chat's timezone"""
+    timezone = get_pref(chat, "timezone") if chat else ""
+    return dt.astimezone(ZoneInfo(timezone)) if timezone else dt
+
+
+def chat_now(chat=None):
+    return to_chat_time(datetime.now(), chat)
+
+
+def is_snoozed(chat, category):
+    return bool(Category(get_pref(chat, "snoozed")) & category)
+
+
+def generate_maps_base_url(lat_long_t):
+    return f"https://www.google.com/maps/search/?api=1&query={lat_long_t[0]}%2C{lat_long_t[1]}"
+
+
+def generate_maps_directions_url(lat_long_t):
+    return f"https://www.google.com/maps/dir/?api=1&destination={lat_long_t[0]}%2C{lat_long_t[1]}"
+
+
+def velox_id(velox):
+    """Short stable id of a velox, small enough for callback data"""
+    return hashlib.sha1(velox.encode('utf-8')).hexdigest()[:12]
+
+
+# shared by all outgoing requests, reusing the connections to the same host
+http_session = requests.Session()
+# identifying the bot is required by the Nominatim usage policy, and polite anyway
+http_session.headers["User-Agent"] = ("luzern-velox-bot "
+                                      "(+https://github.com/aleeraser/luzern-velox-bot)")
+
+
+def http_timeout():
+    """(connect, read) timeouts, a function so that overrides of the constants apply"""
+    return HTTP_CONNECT_TIMEOUT_S, HTTP_TIMEOUT_S
+
+
+def backoff_delay(attempt):
+    """Delay before retry number attempt (from 0), exponential with full jitter"""
+    return random.uniform(0, min(RETRY_MAX_DELAY_S, RETRY_BASE_DELAY_S * 2 ** attempt))
+
+
+# when the last fetch fell back to the cached page, the time that page was fetched
+stale_page = {"fetched_at": None}
+
+
+async def fetch_page(stale_ok=False):
+    """Fetch the HTML of the velox page, or of the offline fixture if set
+
+    If stale_ok, the last page fetched is returned when the website can't be reached,
+    or right away while the circuit is open.
+    """
+    if OFFLINE_FIXTURE:
+        with open(OFFLINE_FIXTURE, 'r', encoding='utf-8') as f:
+            return f.read()
+
+    # don't wait for the retries of a website that's known to be down, the checks
+    # skip it themselves unless forced
+    html = None if stale_ok and not circuit_allows_fetch() else await download_page()
+    if html is not None:
+        with atomic_open(f'{STATE_DIR}/page_cache.html', backup=False) as f:
+            f.write(html)
+        stale_page["fetched_at"] = None
+        return html
+
+    if not stale_ok:
+        return None
+
+    try:
+        with open(f'{STATE_DIR}/page_cache.html', 'r', encoding='utf-8') as f:
+            html = f.read()
+    except FileNotFoundError:
+        return None
+
+    stale_page["fetched_at"] = datetime.fromtimestamp(
+        os.path.getmtime(f'{STATE_DIR}/page_cache.html')).isoformat(timespec='seconds')
+    scrape_log.warning(f"Using the page cached at {stale_page['fetched_at']}")
+    return html
+
+
+async def download_page():
+    for attempt in range(RETRY_ATTEMPTS):
+        try:
+            # in a thread, a slow website mustn't block the handlers and the deliveries
+            response = await asyncio.to_thread(http_session.get, VELOX_LIST_URL,
+                                               timeout=http_timeout())
+        except requests.RequestException as e:
+            scrape_log.warning(f"Failed to make request: {e}")
+        else:
+            if response.status_code == 200:
+                return response.text
+            scrape_log.warning(f"Failed to make request. Status code: {response.status_code}")
+            # client errors won't go away by asking again
+            if response.status_code < 500 and response.status_code != 429:
+                return None
+
+        if attempt < RETRY_ATTEMPTS - 1:
+            await asyncio.sleep(backoff_delay(attempt))
+
+    return None
+
+
+async def fetch_current_dict():
+    """Fetch the current velox list and returns it as a {location_name:maps_url} dict
+
+    Falls back to the cached page, check stale_page to tell.
+    """
+    html = await fetch_page(stale_ok=True)
+    if html is None:
+        return None
+
+    # may geocode veloxes without coordinates, blocking as well
+    return await asyncio.to_thread(parse_velox_list, html)
+
+
+def radar_list_hash(html):
+    """Hash of the list items of the radarList div, None if the page doesn't have it"""
+    start = re.search(r'<div[^>]*\bid=["\']radarList["\']', html)
+    if not start:
+        return None
+
+    # the items may contain divs of their own, the list ends at its </ul>
+    end = html.find('</ul>', start.end())
+    items = re.findall(r'<li\b.*?</li>', html[start.end():end if end != -1 else None],
+                       re.DOTALL)
+    return hashlib.sha256("\n".join(items).encode('utf-8')).hexdigest()
+
+
+# hash of the radarList items the saved list was parsed from, unknown after a restart
+last_page = {"hash": None}
+
+
+def parse_velox_list(html, geocode=True):
+    soup = BeautifulSoup(html, 'html.parser')
+    radar_list_div = soup.find('div', {'id': 'radarList'})
+    if not radar_list_div:
+        scrape_log.error("Could not find div with id 'radarList'")
+        return None
+
+    li_tags = radar_list_div.find_all('li')
+
+    # exclude the last <li> tag since it is a (rather useless) link to the map itself
+    li_tags = li_tags[:-1]
+
+    current_dict = {}
+    for li in li_tags:
+        a_tag = li.find('a')
+
+        # extract and store the text content and coordinates
+        if a_tag:
+            name = normalize_velox_name(a_tag.text)
+            match = re.search(r"map\.flyTo\(\[(.*?),(.*?)\]", a_tag.get('onclick', ''))
+            if match:
+                lat = match.group(1).strip()
+                long = match.group(2).strip()
+                # velox_url = f"https://www.google.com/maps/search/?api=1&query={lat}%2C{long}"
+            else:
+                scrape_log.warning(f"Couldn't retrieve coordinates for {a_tag.text}")
+                # don't hit the geocoder when replaying fixtures or snapshots
+                lat, long = (None, None)
+                if geocode and not OFFLINE_FIXTURE:
+                    lat, long = geocode_velox(name)
+
+            current_dict[name] = (lat, long)
+
+    return current_dict
+
+
+def get_geocode_cache():
+    try:
+        with open(f'{STATE_DIR}/geocoded.json', 'r', encoding='utf-8') as f:
+            return json.load(f)
+    except (FileNotFoundError, ValueError):
+        return {}
+
+
+def save_geocode_cache(cache):
+    with atomic_open(f'{STATE_DIR}/geocoded.json') as f:
+        json.dump(cache, f, indent=2, ensure_ascii=False)
+
+
+# when the last geocoding request was sent, to respect GEOCODE_MIN_INTERVAL_S
+last_geocode = {"time": 0}
+
+
+def geocode_velox(name):
+    """Approximate coordinates of a velox from its name, cached (misses too) by name"""
+    cache = get_geocode_cache()
+    if name in cache:
+        return tuple(cache[name])
+
+    # names are "Municipality, Street (Direction)", the direction only confuses the search
+    municipality, _, street = name.partition(", ")
+    street = re.sub(r"\s*\(.*?\)", "", street)
+    query = f"{street}, {municipality}" if street else name
+
+    # runs in a worker thread while parsing, sleeping doesn't block the bot
+    wait = last_geocode["time"] + GEOCODE_MIN_INTERVAL_S - time.monotonic()
+    if wait > 0:
+        time.sleep(wait)
+    last_geocode["time"] = time.monotonic()
+
+    try:
+        response = http_session.get(GEOCODE_URL, timeout=http_timeout(),
+                                    params={"q": query, "countrycodes": "ch",
+                                            "format": "json", "limit": 1})
+    except requests.RequestException as e:
+        scrape_log.warning(f"Failed to geocode {name}: {e}")
+        return None, None
+
+    if response.status_code != 200:
+        scrape_log.warning(f"Failed to geocode {name}. Status code: {response.status_code}")
+        return None, None
+
+    try:
+        results = response.json()
+    except ValueError as e:
+        scrape_log.warning(f"Failed to geocode {name}, invalid response: {e}")
+        return None, None
+
+    lat_long_t = (results[0]["lat"], results[0]["lon"]) if results else (None, None)
+    if not results:
+        scrape_log.info(f"No geocoding results for {name}")
+
+    cache[name] = lat_long_t
+    save_geocode_cache(cache)
+    return lat_long_t
+
+
+def get_geocoded_velox(velox_dict):
+    """Names of the veloxes whose coordinates come from the geocoder instead of the page"""
+    cache = get_geocode_cache()
+    return {el for el, lat_long_t in velox_dict.items()
+            if None not in lat_long_t and list(cache.get(el, [])) == list(lat_long_t)}
+
+
+async def fetch_route(start, end):
+    """Fetch a driving route between two (lat, long) points as a list of (lat, long)"""
+    url = (f"{OSRM_URL}/route/v1/driving/"
+           f"{start[1]},{start[0]};{end[1]},{end[0]}?overview=full&geometries=geojson")
+
+    try:
+        # in a thread, the handlers mustn't wait for a slow router
+        response = await asyncio.to_thread(http_session.get, url, timeout=http_timeout())
+    except requests.RequestException as e:
+        command_log.warning(f"Failed to request route: {e}")
+        return None
+
+    if response.status_code != 200:
+        command_log.warning(f"Failed to request route. Status code: {response.status_code}")
+        return None
+
+    try:
+        routes = response.json().get("routes")
+        if not routes:
+            command_log.info("No route found")
+            return None
+
+        # GeoJSON coordinates are (long, lat)
+        return [(lat, long) for long, lat in routes[0]["geometry"]["coordinates"]]
+    except (AttributeError, KeyError, TypeError, ValueError) as e:
+        command_log.warning(f"Failed to request route, invalid response: {e}")
+        return None
+
+
+def diff_velox(previous_dict, current_dict):
+    """Return the added and removed veloxes as {location_name:lat_long_t} dicts"""
+    set_previous = set(previous_dict.keys())
+    set_current = set(current_dict.keys())
+
+    added = {el: current_dict[el] for el in set_current - set_previous}
+    removed = {el: previous_dict[el] for el in set_previous - set_current}
+
+    return sort_by_name(added), sort_by_name(removed)
+
+
+def sort_by_name(velox_dict):
+    return dict(sorted(velox_dict.items(), key=lambda item: collation_key(item[0])))
+
+
+COORDINATE_FORMATS = {
+    "decimal": "decimal degrees, e.g. 47.05020, 8.30930",
+    "dms": "degrees, minutes and seconds, e.g. 47°03'00.7\"N 8°18'33.5\"E",
+    "lv95": "Swiss LV95, e.g. 2'666'157, 1'211'386",
+}
+
+DISTANCE_UNITS = ("metric", "imperial")
+
+
+def format_coordinates(lat_long_t, chat):
+    """Coordinates in the chat's format, "" if it has none"""
+    coordinate_format = get_pref(chat, "coordinate_format")
+    if not coordinate_format or None in lat_long_t:
+        return ""
+    if coordinate_format == "dms":
+        return dms_coordinates(lat_long_t)
+    if coordinate_format == "lv95":
+        return swiss_coordinates(*geo.wgs84_to_lv95(lat_long_t))
+    return ", ".join(f"{float(x):.5f}" for x in lat_long_t)
+
+
+def generate_velox_line(el, lat_long_t, chat=None):
+    followed = chat.get("followed", []) if chat else []
+    line = f"- {link(generate_maps_base_url(lat_long_t), el)}"
+
+    if chat and get_pref(chat, "accessible"):
+        if None not in lat_long_t:
+            directions = [tr(chat, el) for el in ("north", "south", "east", "west")]
+            line += tr(chat, ", at {coordinates}",
+                       coordinates=spoken_coordinates(lat_long_t, directions))
+        if el in followed:
+            line += tr(chat, ", followed")
+        return line
+
+    if chat and format_coordinates(lat_long_t, chat):
+        line += f" ({escape(format_coordinates(lat_long_t, chat))})"
+    if el in followed:
+        line += " ⭐"
+
+    return line
+
+
+def generate_home_distance(lat_long_t, chat=None):
+    """How far the velox is from the chat's home location, "" without one"""
+    home = chat.get("home") if chat else None
+    if not home or None in lat_long_t:
+        return ""
+
+    accessible = get_pref(chat, "accessible")
+    distance = format_distance(geo.haversine_distance(home, lat_long_t), spoken=accessible,
+                               chat=chat)
+    return tr(chat, ", about {distance} from you" if accessible else " ≈ {distance} from you",
+              distance=distance)
+
+
+def generate_update_msg(added, removed, chat=None, near_misses=None):
+    """Generate the update message, tailored to the chat's preferences if given"""
+    accessible = chat and get_pref(chat, "accessible")
+
+    msg = tr(chat, UPDATE_HEADER)
+    if added:
+        msg += tr(chat, "Added:\n")
+        for el, lat_long_t in added.items():
+            msg += (generate_velox_line(el, lat_long_t, chat)
+                    + generate_home_distance(lat_long_t, chat) + "\n")
+    if removed:
+        msg += tr(chat, "Removed:\n" if accessible else "🟢 Removed:\n")
+        for el, lat_long_t in removed.items():
+            msg += (generate_velox_line(el, lat_long_t, chat)
+                    + generate_home_distance(lat_long_t, chat) + "\n")
+    if near_misses:
+        max_distance = get_pref(chat, "route_distance")
+        msg += tr(chat, "Just outside your route ({distance}):\n",
+                  distance=format_distance(max_distance, spoken=accessible, chat=chat))
+        for el, (lat_long_t, distance) in near_misses.items():
+            away = tr(chat, "({distance} away)",
+                      distance=format_distance(distance, spoken=accessible, chat=chat))
+            msg += f"{generate_velox_line(el, lat_long_t, chat)} {away}\n"
+    if not added and not removed and not near_misses:
+        msg += tr(chat, "No changes detected.")
+
+    return msg
+
+
+def is_near_route(lat_long_t, chat):
+    route = chat.get("route")
+    # veloxes without coordinates can't be located, better to report them
+    if not route or None in lat_long_t:
+        return True
+
+    max_distance = get_pref(chat, "route_distance")
+    return geo.route_distance(lat_long_t, route) <= max_distance
+
+
+def is_near_home(lat_long_t, chat):
+    home = chat.get("home")
+    radius = get_pref(chat, "home_radius")
+    if not home or not radius or None in lat_long_t:
+        return True
+
+    return geo.haversine_distance(lat_long_t, home) <= radius
+
+
+def velox_municipality(velox):
+    """The website lists veloxes as "<municipality>, <street>"."""
+    return velox.split(",")[0].strip()
+
+
+def is_in_areas(velox, chat):
+    areas = [area.casefold() for area in chat.get("areas", [])]
+    return not areas or velox_municipality(velox).casefold() in areas
+
+
+def filter_velox_for_chat(velox_dict, chat):
+    """Drop the veloxes the chat isn't interested in"""
+    muted = chat.get("muted", [])
+    followed = chat.get("followed", [])
+
+    return {el: lat_long_t for el, lat_long_t in velox_dict.items()
+            if el not in muted and (el in followed
+                                    or (is_near_route(lat_long_t, chat)
+                                        and is_near_home(lat_long_t, chat)
+                                        and is_in_areas(el, chat)))}
+
+
+def near_miss_velox(velox_dict, chat):
+    """Return the veloxes just outside the chat's route, with their distance from it"""
+    route = chat.get("route")
+    soft_distance = get_pref(chat, "soft_route_distance")
+    if not route or not soft_distance:
+        return {}
+
+    max_distance = get_pref(chat, "route_distance")
+    near_misses = {}
+    for el, lat_long_t in velox_dict.items():
+        if el in chat.get("muted", []) or None in lat_long_t:
+            continue
+        distance = geo.route_distance(lat_long_t, route)
+        if max_distance < distance <= soft_distance:
+            near_misses[el] = (lat_long_t, distance)
+
+    return near_misses
+
+
+def generate_mute_keyboard(velox_names, chat):
+    muted = chat.get("muted", [])
+    label = "Mute {velox}" if get_pref(chat, "accessible") else "🔇 mute {velox}"
+    keyboard = [[InlineKeyboardButton(tr(chat, label, velox=el),
+                                      callback_data=f"mute:{velox_id(el)}")]
+                for el in velox_names if el not in muted]
+
+    return InlineKeyboardMarkup(keyboard) if keyboard else None
+
+
+def format_distance(distance, spoken=False, chat=None):
+    if chat and get_pref(chat, "distance_unit") == "imperial":
+        if distance < 1000 * 0.3048:
+            return f"{distance / 0.3048:.0f} {tr(chat, 'feet') if spoken else 'ft'}"
+        return f"{distance / 1609.344:.1f} {tr(chat, 'miles') if spoken else 'mi'}"
+    if distance < 1000:
+        return f"{distance:.0f} {tr(chat, 'meters') if spoken else 'm'}"
+    return f"{distance / 1000:.1f} {tr(chat, 'kilometers') if spoken else 'km'}"
+
+
+def generate_freshness_marker(first_seen, chat=None):
+    """Mark veloxes that appeared this week with 🆕 and the day they appeared
+
+    Screen readers get the day in words instead of the emoji.
+    """
+    if not first_seen:
+        return ""
+
+    accessible = chat and get_pref(chat, "accessible")
+    first_seen = to_chat_time(datetime.fromisoformat(first_seen), chat).date()
+    today = chat_now(chat).date()
+    if first_seen == today:
+        return tr(chat, ", new today") if accessible else f" 🆕 {tr(chat, 'today')}"
+    if first_seen >= today - timedelta(days=today.weekday()):
+        day = tr(chat, WEEKDAYS[first_seen.weekday()])
+        return tr(chat, ", new since {day}", day=day) if accessible else f" 🆕 {day}"
+
+    return ""
+
+
+def generate_current_list_msg(current_dict, history=None, chat=None):
+    msg = tr(chat, "Current List\n\n")
+    for velox, lat_long_t in sort_by_name(current_dict).items():
+        marker = generate_freshness_marker((history or {}).get(velox, {}).get("first_seen"),
+                                           chat)
+        msg += f"- {link(generate_maps_base_url(lat_long_t), velox)}{marker}\n"
+
+    return msg
+
+
+def generate_history_msg(changes, chat=None):
+    msg = tr(chat, "Recent changes\n\n")
+    for change in changes:
+        when = to_chat_time(datetime.fromisoformat(change["time"]), chat).strftime("%d.%m.%Y %H:%M")
+        sign = "+" if change["change"] == "added" else "-"
+        msg += f"{when} {sign} {link(generate_maps_base_url(change['lat_long_t']), change['velox'])}\n"
+    if not changes:
+        msg += tr(chat, "No changes recorded yet.")
+
+    return msg
+
+
+def save_chats(chat_ids):
+    with atomic_open(f'{STATE_DIR}/chat_ids.json') as f:
+        json.dump(chat_ids, f, indent=2)
+
+
+def save_chat_changes(changes, unsubscribed=()):
+    """Apply {chat_id: {key: value}} changes to the chats as they are on disk now
+
+    For code that awaits between reading and saving the chats, a command handled
+    meanwhile (e.g. /start or /settings) would otherwise be overwritten.
+    None values remove the key.
+    """
+    if not changes and not unsubscribed:
+        return
+
+    chat_ids = get_chats() or {}
+    for chat_id in unsubscribed:
+        chat_ids.pop(chat_id, None)
+    for chat_id, fields in changes.items():
+        # unsubscribed meanwhile
+        if chat_id not in chat_ids:
+            continue
+        for key, value in fields.items():
+            if value is None:
+                chat_ids[chat_id].pop(key, None)
+            else:
+                chat_ids[chat_id][key] = value
+    save_chats(chat_ids)
+
+
+def load_configs():
+    try:
+        with open(f'{STATE_DIR}/config.json', 'r', encoding='utf-8') as f:
+            return json.load(f)
+    except (FileNotFoundError, ValueError):
+        return {}
+
+
+# config.json values of the editable settings set by environment variables, None if unset
+env_overrides = {}
+
+
+def apply_overrides(configs):
+    """Apply config.json and the VELOX_<NAME> environment variables, which win
+
+    Tunable constants are set globally, editable settings are set in configs.
+    """
+    for name in TUNABLE_CONSTANTS:
+        value = os.environ.get(f"VELOX_{name}", configs.get(name))
+        if value is None:
+            continue
+
+        try:
+            globals()[name] = type(globals()[name])(value)
+        except ValueError:
+            state_log.warning(f"Ignoring invalid value for {name}: {value!r}")
+            continue
+        state_log.info(f"{name} set to {globals()[name]!r}")
+
+    # /admin_config can still change these at runtime
+    env_overrides.clear()
+    for key, parse in EDITABLE_CONFIGS.items():
+        value = os.environ.get(f"VELOX_{key}", configs.get(key))
+        if value is None:
+            continue
+        if f"VELOX_{key}" in os.environ:
+            env_overrides[key] = configs.get(key)
+
+        try:
+            configs[key] = parse(str(value))
+        except ValueError as e:
+            # the default applies instead
+            state_log.warning(f"Ignoring invalid value for {key}: {e}")
+            configs.pop(key, None)
+
+    PREFERENCES["route_distance"].default = DEFAULT_ROUTE_DISTANCE_M
+
+
+def save_configs(configs):
+    """Save configs, the settings set by environment variables keep their config.json value"""
+    saved = {key: value for key, value in configs.items() if key not in env_overrides}
+    saved.update({key: value for key, value in env_overrides.items() if value is not None})
+    with atomic_open(f'{STATE_DIR}/config.json') as f:
+        json.dump(saved, f, indent=2)
+
+
+# save a new chat_id
+def save_chat_id(chat_id, merge_updates=False, language=DEFAULT_LANGUAGE):
+    chat_id = str(chat_id)
+
+    try:
+        with open(f'{STATE_DIR}/chat_ids.json', 'r', encoding='utf-8') as f:
+            chat_ids = json.load(f)
+    except FileNotFoundError:
+        chat_ids = {}
+
+    if chat_id in chat_ids.keys():
+        return False
+
+    command_log.info(f"New chat {pseudonymize(chat_id)}")
+    # new chats see every current setting in /settings, nothing to announce to them
+    chat_ids[chat_id] = {"preferences_version": PREFERENCES_VERSION}
+    set_pref(chat_ids[chat_id], "merge_updates", merge_updates)
+    set_pref(chat_ids[chat_id], "language", language)
+
+    save_chats(chat_ids)
+    return True
+
+
+def get_chats():
+    try:
+        with open(f'{STATE_DIR}/chat_ids.json', 'r', encoding='utf-8') as f:
+            return json.load(f)
+    except FileNotFoundError:
+        # no previous users
+        return None
+
+
+def get_denylist():
+    try:
+        with open(f'{STATE_DIR}/denylist.json', 'r', encoding='utf-8') as f:
+            return json.load(f)
+    except (FileNotFoundError, ValueError):
+        return {}
+
+
+def save_denylist(denylist):
+    with atomic_open(f'{STATE_DIR}/denylist.json') as f:
+        json.dump(denylist, f, indent=2)
+
+
+def audit(actor, action, details=""):
+    """Append an administrative action to the audit log"""
+    entry = {"time": datetime.now().isoformat(timespec='seconds'),
+             "actor": str(actor), "action": action, "details": details}
+    with open(f'{STATE_DIR}/audit.jsonl', 'a', encoding='utf-8') as f:
+        f.write(json.dumps(entry) + "\n")
+
+
+def get_audit_entries(count):
+    try:
+        with open(f'{STATE_DIR}/audit.jsonl', 'r', encoding='utf-8') as f:
+            lines = f.readlines()
+    except FileNotFoundError:
+        return []
+
+    return [json.loads(line) for line in lines[-count:]]
+
+
+def log_check(started, trigger, current_dict, added=None, removed=None, error=None):
+    """Append the summary of a check cycle to the check log"""
+    entry = {"time": datetime.now().isoformat(timespec='seconds'), "trigger": trigger,
+             "duration_s": round(time.monotonic() - started, 2),
+             "velox": len(current_dict) if current_dict is not None else None,
+             "added": list(added or {}), "removed": list(removed or {}),
+             "error": error}
+    with open(f'{STATE_DIR}/checks.jsonl', 'a', encoding='utf-8') as f:
+        f.write(json.dumps(entry, ensure_ascii=False) + "\n")
+
+
+def get_check_entries(since=None):
+    """Check log entries, only the ones from the datetime since on if given"""
+    try:
+        with open(f'{STATE_DIR}/checks.jsonl', 'r', encoding='utf-8') as f:
+            entries = [json.loads(line) for line in f]
+    except FileNotFoundError:
+        return []
+
+    if since:
+        entries = [e for e in entries if e["time"] >= since.isoformat(timespec='seconds')]
+
+    return entries
+
+
+def save_saved_dict(velox_dict):
+    with atomic_open(f'{STATE_DIR}/previous_dict.json') as f:
+        json.dump(sort_by_name(velox_dict), f)
+
+
+def get_log_salt():
+    """Per-deployment salt for pseudonymized chat ids, generated on first use"""
+    try:
+        with open(f'{STATE_DIR}/.log_salt', 'r', encoding='utf-8') as f:
+            return f.read().strip()
+    except FileNotFoundError:
+        salt = secrets.token_hex(16)
+        with open(f'{STATE_DIR}/.log_salt', 'w', encoding='utf-8') as f:
+            f.write(salt)
+        return salt
+
+
+def pseudonymize(chat_id):
+    """Stable pseudonym of a chat id, used instead of the raw id in logs and audit entries"""
+    return hashlib.sha256(f"{get_log_salt()}{chat_id}".encode('utf-8')).hexdigest()[:12]
+
+
+def get_saved_dict():
+    """Load the list saved by the last update check"""
+    try:
+        with open(f'{STATE_DIR}/previous_dict.json', 'r', encoding='utf-8') as f:
+            return json.load(f)
+    except (FileNotFoundError, ValueError):
+        return {}
+
+
+def get_velox_history():
+    """Every velox ever seen with its coordinates and first_seen/last_seen/removed times"""
+    try:
+        with open(f'{STATE_DIR}/velox_history.json', 'r', encoding='utf-8') as f:
+            return json.load(f)
+    except (FileNotFoundError, ValueError):
+        return {}
+
+
+def save_velox_history(history):
+    with atomic_open(f'{STATE_DIR}/velox_history.json') as f:
+        json.dump(sort_by_name(history), f, indent=2, ensure_ascii=False)
+
+
+def update_velox_history(previous_dict, current_dict, added, removed):
+    """Record the veloxes seen by a check, removed ones stay in the history"""
+    history = get_velox_history()
+    now = datetime.now().isoformat(timespec='seconds')
+    geocoded = get_geocoded_velox(current_dict)
+
+    for el, lat_long_t in current_dict.items():
+        entry = history.setdefault(el, {})
+        entry["lat_long_t"] = lat_long_t
+        entry["geocoded"] = el in geocoded
+        entry["last_seen"] = now
+        entry.pop("removed", None)
+    # the first check can't tell new veloxes from the ones listed for months
+    if previous_dict:
+        for el in added:
+            history[el]["first_seen"] = now
+    for el, lat_long_t in removed.items():
+        history.setdefault(el, {"lat_long_t": lat_long_t})["removed"] = now
+
+    save_velox_history(history)
+
+
+def log_changes(previous_dict, added, removed):
+    """Append the added and removed veloxes to the changelog shown by /history"""
+    # same as the history, the first check would list every velox as added
+    if not previous_dict:
+        return
+
+    now = datetime.now().isoformat(timespec='seconds')
+    with open(f'{STATE_DIR}/changes.jsonl', 'a', encoding='utf-8') as f:
+        for change, veloxes in (("added", added), ("removed", removed)):
+            for el, lat_long_t in veloxes.items():
+                f.write(json.dumps({"time": now, "change": change, "velox": el,
+                                    "lat_long_t": lat_long_t}, ensure_ascii=False) + "\n")
+
+
+def get_recent_changes(count):
+    """The last count changes of the changelog, newest first"""
+    try:
+        with open(f'{STATE_DIR}/changes.jsonl', 'r', encoding='utf-8') as f:
+            changes = [json.loads(line) for line in f]
+    except FileNotFoundError:
+        return []
+
+    return changes[::-1][:count]
+
+
+# the HTTP server reads the changelog from its own thread, a purge rewrites it and
+# the number of purged lines in two steps
+changes_lock = threading.Lock()
+
+
+def get_changes_purged():
+    """Number of changelog lines purged so far"""
+    try:
+        with open(f'{STATE_DIR}/changes_purged', 'r', encoding='utf-8') as f:
+            return int(f.read())
+    except (FileNotFoundError, ValueError):
+        return 0
+
+
+def get_changes_since(cursor, limit):
+    """Up to limit changes after the cursor, oldest first, each one with its own cursor
+
+    Cursors are line numbers of the changelog, which is append only. They count the
+    lines purged from its start too, so they stay valid across purges.
+    """
+    changes = []
+    try:
+        with changes_lock, open(f'{STATE_DIR}/changes.jsonl', 'r', encoding='utf-8') as f:
+            for number, line in enumerate(f, start=get_changes_purged() + 1):
+                if number > cursor:
+                    changes.append({"cursor": number, **json.loads(line)})
+                if len(changes) == limit:
+                    break
+    except FileNotFoundError:
+        pass
+
+    return changes
+
+
+def get_sent_messages():
+    try:
+        with open(f'{STATE_DIR}/sent_messages.json', 'r', encoding='utf-8') as f:
+            return json.load(f)
+    except (FileNotFoundError, ValueError):
+        return {}
+
+
+def save_sent_messages(sent_messages):
+    with atomic_open(f'{STATE_DIR}/sent_messages.json') as f:
+        json.dump(sent_messages, f, indent=2)
+
+
+def track_message(sent_messages, chat, chat_id, message_id, msg, velox_names, buttons,
+                  announced=()):
+    """Remember an update message so it can be edited once its veloxes are removed
+
+    The removal of an announced velox is sent as a reply to it. Only sent_messages
+    is changed, see save_tracked_messages.
+    """
+    chat_messages = sent_messages.setdefault(str(chat_id), [])
+    chat_messages.append({"message_id": message_id,
+                          "text": msg,
+                          # the chat's day, which decides what gets merged
+                          "date": chat_now(chat).date().isoformat(),
+                          "velox": list(velox_names),
+                          "buttons": list(buttons),
+                          "announced": list(announced)})
+    sent_messages[str(chat_id)] = chat_messages[-MAX_TRACKED_MESSAGES:]
+
+
+def save_tracked_messages(changes):
+    """Save the {chat_id: tracked messages} of the chats on top of the ones on disk now
+
+    Chats that left meanwhile (e.g. banned) are skipped.
+    """
+    if not changes:
+        return
+
+    chat_ids = get_chats() or {}
+    sent_messages = get_sent_messages()
+    sent_messages.update({chat_id: chat_messages for chat_id, chat_messages in changes.items()
+                          if chat_id in chat_ids})
+    save_sent_messages(sent_messages)
+
+
+def forget_sent_messages(chat_ids):
+    """Stop tracking the messages of chats that left, they can't be edited anymore"""
+    sent_messages = get_sent_messages()
+    if any(chat_id in sent_messages for chat_id in chat_ids):
+        for chat_id in chat_ids:
+            sent_messages.pop(chat_id, None)
+        save_sent_messages(sent_messages)
+
+
+def find_announcing_message(chat_messages, removed):
+    """Id of the latest message that announced one of the removed veloxes, if any"""
+    for tracked in reversed(chat_messages):
+        if any(el in removed for el in tracked.get("announced", [])):
+            return tracked["message_id"]
+
+    return None
+
+
+async def strike_removed_velox(app, removed):
+    """Strike through removed veloxes in previously sent update messages"""
+    sent_messages = get_sent_messages()
+    chat_ids = get_chats()
+    if not sent_messages or not chat_ids:
+        return
+
+    for chat_id, chat_messages in list(sent_messages.items()):
+        # left before their messages were forgotten on unsubscribing
+        if chat_id not in chat_ids:
+            sent_messages.pop(chat_id)
+            continue
+
+        for tracked in chat_messages:
+            # their removal was just replied to
+            tracked["announced"] = [el for el in tracked.get("announced", []) if el not in removed]
+
+            struck = [el for el in tracked["velox"] if el in removed]
+            if not struck:
+                continue
+
+            for el in struck:
+                tracked["text"] = strike_list_item(tracked["text"], el)
+                tracked["velox"].remove(el)
+                if el in tracked.get("buttons", []):
+                    tracked["buttons"].remove(el)
+
+            try:
+                await app.bot.edit_message_text(chat_id=chat_id,
+                                                message_id=tracked["message_id"],
+                                                text=tracked["text"],
+                                                parse_mode=ParseMode.HTML,
+                                                disable_web_page_preview=True,
+                                                reply_markup=generate_mute_keyboard(
+                                                    tracked.get("buttons", []),
+                                                    chat_ids.get(chat_id, {})))
+            except TelegramError as e:
+                notify_log.warning(f"Could not edit message {tracked['message_id']} "
+                                   f"in chat {pseudonymize(chat_id)}: {e}")
+                tracked["velox"] = []
+
+        # forget messages without any velox left to strike or reply to, unless
+        # they may still get today's updates merged in
+        today = chat_now(chat_ids.get(chat_id)).date().isoformat()
+        sent_messages[chat_id] = [m for m in chat_messages
+                                  if m["velox"] or m["announced"] or m.get("date") == today]
+
+    save_sent_messages(sent_messages)
+
+
+async def merge_into_daily_update(bot, sent_messages, chat, chat_id, msg, velox_names, buttons,
+                                  announced=()):
+    """Append an update to the one already sent today, returns False if there is none
+
+    The tracked message is only updated in sent_messages, see save_tracked_messages.
+    """
+    chat_messages = sent_messages.get(str(chat_id))
+
+    if not chat_messages or chat_messages[-1].get("date") != chat_now(chat).date().isoformat():
+        return False
+
+    tracked = chat_messages[-1]
+    text = tracked["text"] + f"\n{italic(tr(chat, 'Update at {time}', time=f'{chat_now(chat):%H:%M}'))}\n"
+    text += msg.removeprefix(tr(chat, UPDATE_HEADER))
+    if len(text) > MessageLimit.MAX_TEXT_LENGTH:
+        return False
+
+    buttons = tracked.get("buttons", []) + list(buttons)
+
+    try:
+        await send_with_retry(bot.edit_message_text, chat_id=chat_id,
+                              message_id=tracked["message_id"],
+                              text=text, parse_mode=ParseMode.HTML,
+                              disable_web_page_preview=True,
+                              reply_markup=generate_mute_keyboard(buttons, chat))
+    except Forbidden:
+        # the chat is gone, the caller unsubscribes it
+        raise
+    except TelegramError as e:
+        notify_log.warning(f"Could not merge update into message {tracked['message_id']} "
+                           f"in chat {pseudonymize(chat_id)}: {e}")
+        return False
+
+    tracked["text"] = text
+    tracked["velox"] += list(velox_names)
+    tracked["buttons"] = buttons
+    tracked["announced"] = tracked.get("announced", []) + list(announced)
+
+    return True
+
+
+def generate_broadcast_progress_msg(progress):
+    if progress["cancelled"]:
+        msg = "Broadcast cancelled\n\n"
+    elif progress["remaining"]:
+        msg = "Broadcast in progress\n\n"
+    else:
+        msg = "Broadcast done\n\n"
+    msg += (f"Delivered: {progress['delivered']}\n"
+            f"Failed: {progress['failed']}\n"
+            f"Remaining: {progress['remaining']}")
+
+    processed = progress["total"] - progress["remaining"]
+    if processed and progress["remaining"] and not progress["cancelled"]:
+        eta = (time.monotonic() - progress["started"]) / processed * progress["remaining"]
+        msg += f"\nETA: {eta:.0f} s"
+
+    return msg
+
+
+async def report_broadcast_progress(app, progress):
+    """Send or edit the broadcast progress message in every admin chat"""
+    msg = generate_broadcast_progress_msg(progress)
+    keyboard = None
+    if progress["remaining"] and not progress["cancelled"]:
+        keyboard = InlineKeyboardMarkup([[InlineKeyboardButton("✖ cancel",
+                                                               callback_data="broadcast:cancel")]])
+
+    for admin_chat_id in app.bot_data.get("admin_chat_ids", []):
+        try:
+            if admin_chat_id in progress["messages"]:
+                await app.bot.edit_message_text(chat_id=admin_chat_id,
+                                                message_id=progress["messages"][admin_chat_id],
+                                                text=msg, reply_markup=keyboard)
+            else:
+                message = await app.bot.send_message(chat_id=admin_chat_id, text=msg,
+                                                     reply_markup=keyboard)
+                progress["messages"][admin_chat_id] = message.message_id
+        except TelegramError as e:
+            notify_log.warning(f"Failed to report the broadcast progress "
+                               f"in chat {pseudonymize(admin_chat_id)}: {e}")
+
+
+async def notify_admins(app, msg):
+    for admin_chat_id in app.bot_data.get("admin_chat_ids", []):
+        try:
+            await app.bot.send_message(chat_id=admin_chat_id, text=msg)
+        except TelegramError as e:
+            notify_log.warning(f"Failed to notify admin chat {pseudonymize(admin_chat_id)}: {e}")
+
+
+def is_over_hourly_cap(chat):
+    """Whether the chat already got as many updates as it allows in the last hour"""
+    cap = get_pref(chat, "max_per_hour")
+    if not cap:
+        chat.pop("sent_times", None)
+        return False
+
+    hour_ago = time.time() - 3600
+    chat["sent_times"] = [t for t in chat.get("sent_times", []) if t > hour_ago]
+    return len(chat["sent_times"]) >= cap
+
+
+async def send_with_retry(send, **kwargs):
+    """Call a bot method, retrying on flood control and network errors"""
+    for attempt in range(RETRY_ATTEMPTS):
+        try:
+            return await send(**kwargs)
+        except RetryAfter as e:
+            if attempt == RETRY_ATTEMPTS - 1:
+                raise
+            retry_after = e.retry_after
+            if isinstance(retry_after, timedelta):
+                retry_after = retry_after.total_seconds()
+            notify_log.info(f"Flood control, retrying in {retry_after} s")
+            await asyncio.sleep(retry_after + backoff_delay(0))
+        except NetworkError as e:
+            # BadRequest is a NetworkError too, but retrying it would fail the same way
+            if isinstance(e, BadRequest) or attempt == RETRY_ATTEMPTS - 1:
+                raise
+            await asyncio.sleep(backoff_delay(attempt))
+
+
+async def send_held_back_summaries(app):
+    """Tell the chats that hit their hourly cap what they missed, once they can get messages again"""
+    chat_ids = get_chats()
+    if not chat_ids:
+        return
+
+    changes = {}
+    for chat_id, chat in chat_ids.items():
+        if not chat.get("held_back") or is_over_hourly_cap(chat):
+            continue
+
+        held_back = chat["held_back"]
+        try:
+            await send_with_retry(app.bot.send_message, chat_id=chat_id,
+                                  text=tr(chat, "…and {count} more veloxes changed "
+                                                "while you were at your hourly limit, "
+                                                "see /current_list",
+                                          count=len(held_back)))
+        except TelegramError as e:
+            # kept for the next try
+            notify_log.warning(f"Failed to send the held back summary "
+                               f"to chat {pseudonymize(chat_id)}: {e}")
+            continue
+        chat.setdefault("sent_times", []).append(time.time())
+        changes[chat_id] = {"held_back": None, "sent_times": chat["sent_times"]}
+
+    save_chat_changes(changes)
+
+
+async def send_velox_venues(bot, chat_id, velox_dict, chat=None):
+    """Send the veloxes as venues, they open in the user's navigation app"""
+    for velox, lat_long_t in velox_dict.items():
+        if None in lat_long_t:
+            continue
+
+        try:
+            await send_with_retry(bot.send_venue, chat_id=chat_id,
+                                  latitude=float(lat_long_t[0]),
+                                  longitude=float(lat_long_t[1]),
+                                  title=velox,
+                                  address=tr(chat, "Speed camera"),
+                                  disable_notification=True)
+        except TelegramError as e:
+            notify_log.warning(f"Failed to send the venue of {velox} "
+                               f"to chat {pseudonymize(chat_id)}: {e}")
+
+
+@traced("broadcast")
+async def broadcast(app, msg, no_updates, added=None, removed=None, only_chat_ids=None,
+                    category=None, announcement=False):
+    """Send the message to the chats, return the delivered/failed counts"""
+    chat_ids = get_chats() or {}
+    targets = list(chat_ids.keys() if only_chat_ids is None else only_chat_ids)
+
+    # /admin_cancel, the cancel button or a shutdown set "cancelled"
+    progress = {"total": len(targets), "remaining": len(targets),
+                "delivered": 0, "failed": 0, "cancelled": False,
+                "started": time.monotonic(), "messages": {}}
+
+    if not targets:
+        return progress
+
+    app.bot_data["broadcast_progress"] = progress
+    report = len(targets) >= BROADCAST_PROGRESS_MIN_CHATS
+    if report:
+        await report_broadcast_progress(app, progress)
+
+    # saved at the end on top of the chats as they are then, see save_chat_changes
+    changes = {}
+    unsubscribed = []
+    send_failures = app.bot_data.setdefault("send_failures", {})
+    # saved once at the end like the chat changes, for the chats in tracked
+    sent_messages = get_sent_messages() if added or removed else {}
+    tracked = set()
+    for i, chat_id in enumerate(targets):
+        progress["remaining"] = len(targets) - i
+        if progress["cancelled"]:
+            notify_log.info(f"Broadcast cancelled, {progress['remaining']} chats left")
+            break
+        if report and i and i % BROADCAST_PROGRESS_EVERY == 0:
+            await report_broadcast_progress(app, progress)
+
+        chat_id = str(chat_id)
+        chat = chat_ids.get(chat_id, {})
+        if no_updates and (not get_pref(chat, "notify_for_no_updates")
+                           or time.time() - chat.get("last_no_updates", 0)
+                           < get_pref(chat, "no_updates_every_h") * 3600):
+            continue
+        # chats with a pinned list only get their pinned message edited
+        if chat.get("pinned_message_id") and not announcement:
+            continue
+        if category and is_snoozed(chat, category):
+            continue
+
+        keyboard = None
+        buttons = []
+        if added or removed:
+            chat_added = filter_velox_for_chat(added, chat)
+            chat_removed = filter_velox_for_chat(removed, chat)
+            if is_snoozed(chat, Category.REMOVALS):
+                chat_removed = {}
+
+            if (chat_added or chat_removed) and is_over_hourly_cap(chat):
+                held_back = chat.setdefault("held_back", [])
+                held_back += [el for el in {**chat_added, **chat_removed} if el not in held_back]
+                changes.setdefault(chat_id, {})["held_back"] = held_back
+                continue
+
+            # near misses are reported at most once a day
+            near_misses = {}
+            if (not is_snoozed(chat, Category.NEAR_MISSES)
+                    and chat.get("last_near_miss") != chat_now(chat).date().isoformat()):
+                near_misses = near_miss_velox(added, chat)
+                if near_misses:
+                    chat["last_near_miss"] = chat_now(chat).date().isoformat()
+                    changes.setdefault(chat_id, {})["last_near_miss"] = chat["last_near_miss"]
+
+            if not chat_added and not chat_removed and not near_misses:
+                continue
+
+            chat_msg = generate_update_msg(chat_added, chat_removed, chat, near_misses)
+            buttons = [el for el in chat_added if el not in chat.get("followed", [])]
+            keyboard = generate_mute_keyboard(buttons, chat)
+        else:
+            chat_added = chat_removed = {}
+            # announcements go out as written, status messages in the chat's language
+            if announcement:
+                chat_msg = msg
+            elif category == Category.FAILURES:
+                chat_msg = tr(chat, msg)
+            else:
+                chat_msg = generate_update_msg({}, {}, chat)
+
+        strike = chat_added and get_pref(chat, "strike_removed")
+        merge = (chat_added or chat_removed) and get_pref(chat, "merge_updates")
+        velox_names = list(chat_added) if strike else []
+
+        try:
+            # a failed merge falls back to a new message
+            merged = merge and await merge_into_daily_update(app.bot, sent_messages, chat,
+                                                             chat_id, chat_msg, velox_names,
+                                                             buttons, chat_added)
+            if not merged:
+                # removals reply to the message that announced the velox, if there's one
+                reply_to = None
+                if chat_removed:
+                    reply_to = find_announcing_message(sent_messages.get(chat_id, []),
+                                                       chat_removed)
+                message = await send_with_retry(app.bot.send_message, chat_id=chat_id,
+                                                text=chat_msg, parse_mode=ParseMode.HTML,
+                                                disable_web_page_preview=True,
+                                                reply_markup=keyboard,
+                                                reply_to_message_id=reply_to,
+                                                allow_sending_without_reply=True)
+        except Forbidden as e:
+            # blocked by the user or kicked from the group, sending again would fail forever
+            notify_log.info(f"Unsubscribing chat {pseudonymize(chat_id)}: {e}")
+            audit("bot", "auto_unsubscribe", pseudonymize(chat_id))
+            unsubscribed.append(chat_id)
+            progress["failed"] += 1
+            continue
+        except TelegramError as e:
+            failures = send_failures.get(chat_id, 0) + 1
+            send_failures[chat_id] = failures
+            # errors are reported to Sentry, single failures are usually transient
+            level = logging.ERROR if failures == REPORTED_SEND_FAILURES else logging.WARNING
+            notify_log.log(level, f"Failed to send the update to chat {pseudonymize(chat_id)} "
+                                  f"(failure {failures} in a row): {e}")
+            progress["failed"] += 1
+            continue
+
+        send_failures.pop(chat_id, None)
+        progress["delivered"] += 1
+        if merged:
+            tracked.add(chat_id)
+            if chat_added and get_pref(chat, "send_venues"):
+                await send_velox_venues(app.bot, chat_id, chat_added, chat)
+            continue
+        if get_pref(chat, "max_per_hour") and (chat_added or chat_removed):
+            chat.setdefault("sent_times", []).append(time.time())
+            changes.setdefault(chat_id, {})["sent_times"] = chat["sent_times"]
+        if no_updates and get_pref(chat, "no_updates_every_h"):
+            chat["last_no_updates"] = time.time()
+            changes.setdefault(chat_id, {})["last_no_updates"] = chat["last_no_updates"]
+        # the ones a later update edits, or replies to when their veloxes are removed
+        if strike or merge or (chat_added and not is_snoozed(chat, Category.REMOVALS)):
+            track_message(sent_messages, chat, chat_id, message.message_id, chat_msg,
+                          velox_names, buttons, chat_added)
+            tracked.add(chat_id)
+        if chat_added and get_pref(chat, "send_venues"):
+            await send_velox_venues(app.bot, chat_id, chat_added, chat)
+
+    if not progress["cancelled"]:
+        progress["remaining"] = 0
+    if report:
+        await report_broadcast_progress(app, progress)
+    app.bot_data.pop("broadcast_progress", None)
+
+    # chats already notified must not get the near misses again
+    save_chat_changes(changes, unsubscribed)
+    save_tracked_messages({chat_id: sent_messages[chat_id] for chat_id in tracked})
+    forget_sent_messages(unsubscribed)
+
+    return progress
+
+
+async def archive_notification(app, msg):
+    """Mirror a sent notification into the operator's archive chat, if configured"""
+    archive_chat_id = app.bot_data.get("configs", {}).get("ARCHIVE_CHAT_ID")
+    if not archive_chat_id:
+        return
+
+    msg = msg.rstrip() + f"\n\n{italic(f'Sent {datetime.now():%d.%m.%Y %H:%M}')}"
+    try:
+        await app.bot.send_message(chat_id=archive_chat_id, text=msg,
+                                   parse_mode=ParseMode.HTML,
+                                   disable_web_page_preview=True,
+                                   disable_notification=True)
+    except TelegramError as e:
+        notify_log.warning(f"Failed to archive the notification: {e}")
+
+
+def generate_pinned_list_msg(current_dict, chat=None):
+    msg = generate_current_list_msg(current_dict, chat=chat)
+    msg += f"\n{italic(tr(chat, 'Last updated: {time}', time=f'{chat_now(chat):%d.%m.%Y %H:%M}'))}"
+
+    return msg
+
+
+async def send_pinned_list(bot, chat_id, current_dict, chat=None):
+    """Send the current list and pin it, returning the new message id"""
+    msg = generate_pinned_list_msg(current_dict, chat)
+
+    message = await bot.send_message(chat_id=chat_id, text=msg,
+                                     parse_mode=ParseMode.HTML,
+                                     disable_web_page_preview=True)
+    await bot.pin_chat_message(chat_id=chat_id, message_id=message.message_id,
+                               disable_notification=True)
+
+    return message.message_id
+
+
+async def update_pinned_lists(app, current_dict):
+    """Edit the pinned list of every chat that has one"""
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return
+
+    changes = {}
+    for chat_id, chat in chat_ids.items():
+        message_id = chat.get("pinned_message_id")
+        if not message_id:
+            continue
+
+        msg = generate_pinned_list_msg(current_dict, chat)
+
+        try:
+            await app.bot.edit_message_text(chat_id=chat_id, message_id=message_id,
+                                            text=msg, parse_mode=ParseMode.HTML,
+                                            disable_web_page_preview=True)
+        except BadRequest as e:
+            # the pinned message is gone (e.g. deleted by a chat admin), pin a new one
+            notify_log.warning(f"Could not edit pinned list in chat {pseudonymize(chat_id)}: {e}")
+            try:
+                changes[chat_id] = {"pinned_message_id": await send_pinned_list(
+                    app.bot, chat_id, current_dict, chat)}
+            except TelegramError as e:
+                notify_log.warning(f"Could not pin a new list in chat {pseudonymize(chat_id)}: {e}")
+        except TelegramError as e:
+            # e.g. the bot was removed from the group, the other chats still get theirs
+            notify_log.warning(f"Could not edit pinned list in chat {pseudonymize(chat_id)}: {e}")
+
+    save_chat_changes(changes)
+
+
+# command to handle /start
+async def cmd_start(update: Update,
+                    context: ContextTypes.DEFAULT_TYPE):
+    chat_id = update.message.chat_id
+    configs = context.bot_data.get("configs", {})
+
+    # start in the user's Telegram language when there's a translation for it
+    language = user_language(update.effective_user)
+
+    newly_subscribed = save_chat_id(chat_id, configs.get("MERGE_UPDATES_DEFAULT", False),
+                                    language)
+
+    # deep links like t.me/<bot>?start=segment_beta also join a segment
+    payload = context.args[0] if context.args else ""
+    segment = payload.removeprefix("segment_")
+    if payload.startswith("segment_") and segment in configs.get("SEGMENTS", []):
+        chat_ids = get_chats()
+        if join_segment(chat_ids[str(chat_id)], segment):
+            save_chats(chat_ids)
+            audit(pseudonymize(chat_id), "join_segment", segment)
+
+    chat = get_chats()[str(chat_id)]
+    msg = tr(chat, "You're subscribed to updates.")
+    if not newly_subscribed:
+        msg = tr(chat, "Already subscribed.")
+    await context.bot.send_message(chat_id=chat_id,
+                                   text=msg)
+
+
+# command to handle /current_list
+async def cmd_current_list(update: Update,
+                           context: ContextTypes.DEFAULT_TYPE):
+
+    chat = (get_chats() or {}).get(str(update.message.chat_id))
+    current_dict = await fetch_current_dict()
+    if current_dict is None:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Failed to fetch the current list, "
+                                                     "please try again later."))
+        return None
+
+    msg = generate_current_list_msg(current_dict, get_velox_history(), chat)
+    if stale_page["fetched_at"]:
+        fetched_at = to_chat_time(datetime.fromisoformat(stale_page["fetched_at"]), chat)
+        msg += "\n" + italic(tr(chat, "The police website can't be reached, "
+                                      "this is the list of {time}.",
+                                 time=f"{fetched_at:%d.%m.%Y %H:%M}"))
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML,
+                                   disable_web_page_preview=True)
+
+
+# command to handle /history
+async def cmd_history(update: Update,
+                      context: ContextTypes.DEFAULT_TYPE):
+    try:
+        count = int(context.args[0]) if context.args else HISTORY_DEFAULT_ENTRIES
+    except ValueError:
+        count = HISTORY_DEFAULT_ENTRIES
+
+    chat = (get_chats() or {}).get(str(update.message.chat_id))
+    msg = generate_history_msg(get_recent_changes(min(max(count, 1), HISTORY_MAX_ENTRIES)),
+                               chat)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML,
+                                   disable_web_page_preview=True)
+
+
+# command to handle /show_map
+async def cmd_show_map(update: Update,
+                       context: ContextTypes.DEFAULT_TYPE):
+
+    chat = (get_chats() or {}).get(str(update.message.chat_id))
+    current_dict = await fetch_current_dict()
+    if current_dict is None:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Failed to fetch the current list, "
+                                                     "please try again later."))
+        return None
+
+    url = "https://www.google.com/maps/dir/"
+    # hardcoded coords of Luzern for map centering
+    url_suffix = "//@47.0473835,8.2532969,12.25z"
+
+    for _, lat_long_t in current_dict.items():
+        url += f"{lat_long_t[0]},{lat_long_t[1]}/"
+    url += url_suffix
+
+    msg = f"{tr(chat, 'Velox map')}\n{url}"
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML,
+                                   disable_web_page_preview=True)
+
+
+# command to handle /export_geojson
+async def cmd_export_geojson(update: Update,
+                             context: ContextTypes.DEFAULT_TYPE):
+    chat = (get_chats() or {}).get(str(update.message.chat_id))
+    current_dict = await fetch_current_dict()
+    if current_dict is None:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Failed to fetch the current list, "
+                                                     "please try again later."))
+        return None
+
+    geojson = export.to_geojson(sort_by_name(current_dict), get_geocoded_velox(current_dict))
+    await context.bot.send_document(chat_id=update.message.chat_id,
+                                    document=geojson.encode(),
+                                    filename=f"veloxes-{date.today()}.geojson")
+
+
+# command to handle /manual_update
+async def cmd_manual_update(update: Update,
+                            context: ContextTypes.DEFAULT_TYPE):
+    # the check would be skipped without a word
+    if check_lock.locked():
+        chat = (get_chats() or {}).get(str(update.message.chat_id))
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "An update check is already running, "
+                                                     "please try again in a moment."))
+        return None
+
+    return await check_for_updates(context.application, forced_update=True, trigger="manual")
+
+
+def generate_toggle_command(key):
+    """Create the handler of the command toggling a boolean preference"""
+    async def cmd_toggle(update: Update,
+                         context: ContextTypes.DEFAULT_TYPE):
+        chat_id = str(update.message.chat_id)
+        chat_ids = get_chats()
+
+        if not chat_ids:
+            return None
+
+        new_val = not get_pref(chat_ids[chat_id], key)
+        set_pref(chat_ids[chat_id], key, new_val)
+
+        save_chats(chat_ids)
+
+        msg = PREFERENCES[key].enabled_msg if new_val else PREFERENCES[key].disabled_msg
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat_ids[chat_id], msg))
+
+    return cmd_toggle
+
+
+def generate_settings_keyboard(chat):
+    keyboard = [[InlineKeyboardButton(f"{'✅' if get_pref(chat, key) else '⬜'} {tr(chat, pref.label)}",
+                                      callback_data=f"pref:{key}")]
+                for key, pref in PREFERENCES.items() if pref.label]
+
+    return InlineKeyboardMarkup(keyboard)
+
+
+# command to handle /settings
+async def cmd_settings(update: Update,
+                       context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat_ids[chat_id], "Tap a setting to toggle it:"),
+                                   reply_markup=generate_settings_keyboard(chat_ids[chat_id]))
+
+
+# handle the buttons of the /settings menu
+async def on_settings_button(update: Update,
+                             context: ContextTypes.DEFAULT_TYPE):
+    query = update.callback_query
+    chat_id = str(query.message.chat_id)
+    chat_ids = get_chats()
+    key = query.data.removeprefix("pref:")
+
+    if not chat_ids or chat_id not in chat_ids or key not in PREFERENCES:
+        await query.answer(translate(user_language(query.from_user), "You're not subscribed."))
+        return None
+
+    new_val = not get_pref(chat_ids[chat_id], key)
+    set_pref(chat_ids[chat_id], key, new_val)
+    save_chats(chat_ids)
+
+    await query.edit_message_reply_markup(generate_settings_keyboard(chat_ids[chat_id]))
+    msg = PREFERENCES[key].enabled_msg if new_val else PREFERENCES[key].disabled_msg
+    await query.answer(tr(chat_ids[chat_id], msg))
+
+
+def generate_language_keyboard():
+    keyboard = [[InlineKeyboardButton(name, callback_data=f"language:{language}")]
+                for language, name in LANGUAGES.items()]
+
+    return InlineKeyboardMarkup(keyboard)
+
+
+# command to handle /language
+async def cmd_language(update: Update,
+                       context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat_ids[chat_id], "Choose your language:"),
+                                   reply_markup=generate_language_keyboard())
+
+
+# handle the buttons of the /language menu
+async def on_language_button(update: Update,
+                             context: ContextTypes.DEFAULT_TYPE):
+    query = update.callback_query
+    chat_id = str(query.message.chat_id)
+    chat_ids = get_chats()
+    language = query.data.removeprefix("language:")
+
+    if not chat_ids or chat_id not in chat_ids or language not in LANGUAGES:
+        await query.answer(translate(user_language(query.from_user), "You're not subscribed."))
+        return None
+
+    set_pref(chat_ids[chat_id], "language", language)
+    save_chats(chat_ids)
+
+    await query.edit_message_text(tr(chat_ids[chat_id], "Language set to {name}.",
+                                     name=LANGUAGES[language]))
+    await query.answer()
+
+
+def generate_snooze_keyboard(chat):
+    keyboard = [[InlineKeyboardButton(f"{'🔕' if is_snoozed(chat, category) else '🔔'} "
+                                      f"{tr(chat, label)}",
+                                      callback_data=f"snooze:{category.value}")]
+                for category, label in CATEGORY_LABELS.items()]
+
+    return InlineKeyboardMarkup(keyboard)
+
+
+# command to handle /snooze
+async def cmd_snooze(update: Update,
+                     context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat_ids[chat_id],
+                                           "Tap a category to snooze or unsnooze it:"),
+                                   reply_markup=generate_snooze_keyboard(chat_ids[chat_id]))
+
+
+# handle the buttons of the /snooze menu
+async def on_snooze_button(update: Update,
+                           context: ContextTypes.DEFAULT_TYPE):
+    query = update.callback_query
+    chat_id = str(query.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids or chat_id not in chat_ids:
+        await query.answer(translate(user_language(query.from_user), "You're not subscribed."))
+        return None
+
+    category = Category(int(query.data.removeprefix("snooze:")))
+    snoozed = Category(get_pref(chat_ids[chat_id], "snoozed")) ^ category
+    set_pref(chat_ids[chat_id], "snoozed", snoozed.value)
+    save_chats(chat_ids)
+
+    await query.edit_message_reply_markup(generate_snooze_keyboard(chat_ids[chat_id]))
+    state = "{label} snoozed" if snoozed & category else "{label} unsnoozed"
+    await query.answer(tr(chat_ids[chat_id], state,
+                          label=tr(chat_ids[chat_id], CATEGORY_LABELS[category])))
+
+
+# command to handle /pin_list
+async def cmd_set_pin_list(update: Update,
+                           context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    message_id = chat.get("pinned_message_id")
+    if message_id:
+        chat["pinned_message_id"] = None
+        save_chats(chat_ids)
+        try:
+            await context.bot.unpin_chat_message(chat_id=chat_id, message_id=message_id)
+        except BadRequest as e:
+            command_log.warning(f"Could not unpin list in chat {pseudonymize(chat_id)}: {e}")
+
+        msg = tr(chat, "Disabled - updates are sent as new messages again")
+    else:
+        current_dict = await fetch_current_dict()
+        if current_dict is None:
+            msg = tr(chat, "Failed to fetch the current list, please try again later.")
+        else:
+            try:
+                message_id = await send_pinned_list(context.bot, chat_id, current_dict, chat)
+                save_chat_changes({chat_id: {"pinned_message_id": message_id}})
+                msg = tr(chat, "Enabled - the pinned list is edited on every change")
+            except BadRequest as e:
+                command_log.warning(f"Could not pin list in chat {pseudonymize(chat_id)}: {e}")
+                msg = tr(chat, "Could not pin the list. Make sure I'm allowed to pin messages.")
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+def find_velox(query, velox_names):
+    """Return the veloxes matching a user query, preferring an exact match"""
+    exact = [el for el in velox_names if el.lower() == query.lower()]
+    if exact:
+        return exact
+
+    return [el for el in velox_names if query.lower() in el.lower()]
+
+
+# command to handle /follow
+async def cmd_follow(update: Update,
+                     context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    followed = chat.get("followed", [])
+    query = " ".join(context.args)
+
+    if not query:
+        msg = tr(chat, "Usage: /follow &lt;velox name&gt;\n\n")
+        if followed:
+            msg += tr(chat, "Followed veloxes:\n")
+            msg += bullet_list(sorted(followed, key=collation_key))
+        else:
+            msg += tr(chat, "You're not following any velox.")
+    else:
+        current_dict = await fetch_current_dict()
+        # other commands may have changed the chat meanwhile, e.g. another /follow
+        chat = (get_chats() or {}).get(chat_id, chat)
+        followed = chat.get("followed", [])
+        if current_dict is None:
+            msg = tr(chat, "Failed to fetch the current list, please try again later.")
+        else:
+            matches = find_velox(query, current_dict.keys())
+            if not matches:
+                msg = tr(chat, "No velox matching '{query}' in the current list.",
+                         query=escape(query))
+            elif len(matches) > 1:
+                msg = tr(chat, "Multiple veloxes match, please be more specific:\n")
+                msg += bullet_list(matches)
+            elif matches[0] in followed:
+                msg = tr(chat, "Already following {velox}.", velox=escape(matches[0]))
+            else:
+                followed.append(matches[0])
+                save_chat_changes({chat_id: {"followed": followed}})
+                msg = tr(chat, "Following {velox} - it will be marked with ⭐ in updates.",
+                         velox=escape(matches[0]))
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML)
+
+
+# command to handle /unfollow
+async def cmd_unfollow(update: Update,
+                       context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    followed = chat.get("followed", [])
+    matches = find_velox(" ".join(context.args), followed)
+
+    if not context.args or not matches:
+        msg = tr(chat, "Usage: /unfollow &lt;followed velox name&gt;")
+    elif len(matches) > 1:
+        msg = tr(chat, "Multiple followed veloxes match, please be more specific:\n")
+        msg += bullet_list(matches)
+    else:
+        followed.remove(matches[0])
+        chat["followed"] = followed
+        save_chats(chat_ids)
+        msg = tr(chat, "No longer following {velox}.", velox=escape(matches[0]))
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML)
+
+
+# handle the "mute" buttons of update messages
+async def on_mute_button(update: Update,
+                         context: ContextTypes.DEFAULT_TYPE):
+    query = update.callback_query
+    chat_id = str(query.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids or chat_id not in chat_ids:
+        await query.answer(translate(user_language(query.from_user), "You're not subscribed."))
+        return None
+
+    pressed_id = query.data.removeprefix("mute:")
+    matches = [el for el in get_saved_dict() if velox_id(el) == pressed_id]
+    if not matches:
+        await query.answer(tr(chat_ids[chat_id], "This velox is no longer listed."))
+        return None
+
+    velox = matches[0]
+    muted = chat_ids[chat_id].get("muted", [])
+    if velox not in muted:
+        muted.append(velox)
+    chat_ids[chat_id]["muted"] = muted
+
+    # a muted velox can't be followed at the same time
+    followed = chat_ids[chat_id].get("followed", [])
+    if velox in followed:
+        followed.remove(velox)
+
+    save_chats(chat_ids)
+
+    keyboard = [row for row in query.message.reply_markup.inline_keyboard
+                if row[0].callback_data != query.data]
+    await query.edit_message_reply_markup(InlineKeyboardMarkup(keyboard) if keyboard else None)
+    await query.answer(tr(chat_ids[chat_id], "Muted {velox}. Use /unmute to undo.", velox=velox))
+
+
+# command to handle /unmute
+async def cmd_unmute(update: Update,
+                     context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    muted = chat.get("muted", [])
+    matches = find_velox(" ".join(context.args), muted)
+
+    if not context.args or not matches:
+        msg = tr(chat, "Usage: /unmute &lt;muted velox name&gt;\n\n")
+        if muted:
+            msg += tr(chat, "Muted veloxes:\n")
+            msg += bullet_list(sorted(muted, key=collation_key))
+        else:
+            msg += tr(chat, "You haven't muted any velox.")
+    elif len(matches) > 1:
+        msg = tr(chat, "Multiple muted veloxes match, please be more specific:\n")
+        msg += bullet_list(matches)
+    else:
+        muted.remove(matches[0])
+        chat["muted"] = muted
+        save_chats(chat_ids)
+        msg = tr(chat, "Unmuted {velox}.", velox=escape(matches[0]))
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML)
+
+
+# handle shared (non-live) locations
+async def on_location(update: Update,
+                      context: ContextTypes.DEFAULT_TYPE):
+    location = update.message.location
+    # live locations keep updating, only answer to plain pins
+    if location.live_period:
+        return None
+
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats() or {}
+    if chat_ids.get(chat_id, {}).pop("awaiting_home", False):
+        await save_home(update, context, chat_ids, (location.latitude, location.longitude))
+        return None
+
+    chat = chat_ids.get(chat_id)
+    current_dict = await fetch_current_dict()
+    if current_dict is None:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Failed to fetch the current list, "
+                                                     "please try again later."))
+        return None
+
+    point = (location.latitude, location.longitude)
+    nearby = sorted((geo.haversine_distance(point, lat_long_t), velox, lat_long_t)
+                    for velox, lat_long_t in current_dict.items() if None not in lat_long_t)
+    nearby = [el for el in nearby if el[0] <= NEARBY_DISTANCE_M]
+
+    if not nearby:
+        msg = tr(chat, "No veloxes within {distance} of this location.",
+                 distance=format_distance(NEARBY_DISTANCE_M, chat=chat))
+    else:
+        msg = tr(chat, "Veloxes within {distance}\n\n",
+                 distance=format_distance(NEARBY_DISTANCE_M, chat=chat))
+        for distance, velox, lat_long_t in nearby:
+            msg += (f"{generate_velox_line(velox, lat_long_t, chat)} "
+                    f"({format_distance(distance, chat=chat)})\n")
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML,
+                                   disable_web_page_preview=True,
+                                   reply_to_message_id=update.message.message_id)
+
+
+# command to handle /nearest
+async def cmd_nearest(update: Update,
+                      context: ContextTypes.DEFAULT_TYPE):
+    chat = (get_chats() or {}).get(str(update.message.chat_id))
+
+    if not chat or not chat.get("home"):
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "/nearest lists the veloxes closest to "
+                                                     "your home location, save it with "
+                                                     "/set_home first. Or share any location "
+                                                     "to see the veloxes around it."))
+        return None
+
+    args = [arg for arg in context.args if arg != "map"]
+    try:
+        count = int(args[0]) if args else NEAREST_DEFAULT_COUNT
+        if count < 1:
+            raise ValueError
+    except ValueError:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /nearest [count] [map]\n"
+                                                     "e.g. /nearest 5 map also sends the "
+                                                     "5 veloxes as map pins"))
+        return None
+
+    current_dict = await fetch_current_dict()
+    if current_dict is None:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Failed to fetch the current list, "
+                                                     "please try again later."))
+        return None
+
+    nearest = sorted((geo.haversine_distance(chat["home"], lat_long_t), velox, lat_long_t)
+                     for velox, lat_long_t in current_dict.items() if None not in lat_long_t)
+    nearest = nearest[:min(count, NEAREST_MAX_COUNT)]
+
+    msg = tr(chat, "No veloxes with known coordinates right now.")
+    if nearest:
+        msg = tr(chat, "Closest veloxes to your home\n\n")
+        for distance, velox, lat_long_t in nearest:
+            msg += (f"{generate_velox_line(velox, lat_long_t, chat)} "
+                    f"({format_distance(distance, chat=chat)})\n")
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML,
+                                   disable_web_page_preview=True)
+    if "map" in context.args:
+        await send_velox_venues(context.bot, update.message.chat_id,
+                                {velox: lat_long_t for _, velox, lat_long_t in nearest}, chat)
+
+
+# handle inline queries ("@bot near me" or "@bot <street>")
+async def on_inline_query(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    query = update.inline_query
+    text = query.query.strip()
+
+    # answer from the last saved list, inline queries arrive at every keystroke
+    saved_dict = {velox: lat_long_t for velox, lat_long_t in get_saved_dict().items()
+                  if None not in lat_long_t}
+
+    distances = {}
+    if query.location and text.lower() in ("", "near me"):
+        point = (query.location.latitude, query.location.longitude)
+        distances = {velox: geo.haversine_distance(point, lat_long_t)
+                     for velox, lat_long_t in saved_dict.items()}
+        matches = sorted(distances, key=distances.get)
+    elif text and text.lower() != "near me":
+        matches = find_velox(text, saved_dict.keys())
+    else:
+        matches = list(saved_dict)
+
+    # inline queries come from users, their private chat has the same id
+    chat = (get_chats() or {}).get(str(query.from_user.id))
+    results = []
+    for velox in matches[:MAX_INLINE_RESULTS]:
+        lat_long_t = saved_dict[velox]
+        address = tr(chat, "Speed camera")
+        if velox in distances:
+            address += tr(chat, ", {distance} away",
+                          distance=format_distance(distances[velox], chat=chat))
+        if chat and format_coordinates(lat_long_t, chat):
+            address += f", {format_coordinates(lat_long_t, chat)}"
+
+        navigate = InlineKeyboardButton(tr(chat, "🧭 Navigate"),
+                                        url=generate_maps_directions_url(lat_long_t))
+        results.append(InlineQueryResultVenue(id=velox_id(velox),
+                                              latitude=float(lat_long_t[0]),
+                                              longitude=float(lat_long_t[1]),
+                                              title=velox,
+                                              address=address,
+                                              reply_markup=InlineKeyboardMarkup([[navigate]])))
+
+    # units and coordinates follow the chat preferences, so the answer is personal too
+    await query.answer(results, cache_time=300, is_personal=bool(distances) or chat is not None)
+
+
+# handle uploaded GPX tracks
+async def on_gpx_upload(update: Update,
+                        context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids or chat_id not in chat_ids:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=translate(user_language(update.effective_user),
+                                                      "Please subscribe with /start first."))
+        return None
+
+    gpx_file = await context.bot.get_file(update.message.document.file_id)
+    try:
+        route = geo.parse_gpx(bytes(await gpx_file.download_as_bytearray()))
+    except (ET.ParseError, TypeError, ValueError) as e:
+        command_log.info(f"Invalid GPX file from chat {pseudonymize(chat_id)}: {e}")
+        route = []
+
+    chat = chat_ids[chat_id]
+    if not route:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Couldn't find any track in this GPX file."))
+        return None
+
+    chat["route"] = geo.simplify_route(route)
+    # on top of the chat as it is after the download
+    save_chat_changes({chat_id: {"route": chat["route"]}})
+
+    max_distance = get_pref(chat, "route_distance")
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat, "Route saved - you'll only be notified about "
+                                                 "veloxes within {distance} of it.\n"
+                                                 "Use /route_distance to change the distance "
+                                                 "and /clear_route to remove the route.",
+                                           distance=format_distance(max_distance, chat=chat)))
+
+
+def parse_lat_long(text):
+    """Parse a "lat,long" string, returns None if invalid"""
+    try:
+        lat, long = (float(x) for x in text.split(','))
+    except ValueError:
+        return None
+
+    if not -90 <= lat <= 90 or not -180 <= long <= 180:
+        return None
+
+    return lat, long
+
+
+# command to handle /commute
+async def cmd_commute(update: Update,
+                      context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    points = [parse_lat_long(arg) for arg in context.args]
+    if len(points) != 2 or None in points:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /commute <home lat,long> "
+                                                     "<work lat,long>\n"
+                                                     "e.g. /commute 47.0502,8.3093 "
+                                                     "47.0165,8.3149"))
+        return None
+
+    route = await fetch_route(points[0], points[1])
+    if not route:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Couldn't compute a route between "
+                                                     "these points."))
+        return None
+
+    # on top of the chat as it is after the request
+    save_chat_changes({chat_id: {"commute": {"home": points[0], "work": points[1]},
+                                 "route": geo.simplify_route(route)}})
+
+    max_distance = get_pref(chat, "route_distance")
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat, "Commute saved - you'll only be notified about "
+                                                 "veloxes within {distance} of it.\n"
+                                                 "Use /route_distance to change the distance "
+                                                 "and /clear_route to remove the route.",
+                                           distance=format_distance(max_distance, chat=chat)))
+
+
+# command to handle /route_distance
+async def cmd_route_distance(update: Update,
+                             context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    try:
+        max_distance = int(context.args[0])
+        if max_distance <= 0:
+            raise ValueError
+    except (IndexError, ValueError):
+        max_distance = get_pref(chat, "route_distance")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /route_distance <meters>\n\n"
+                                                     "Current distance: {distance}",
+                                               distance=format_distance(max_distance, chat=chat)))
+        return None
+
+    set_pref(chat, "route_distance", max_distance)
+    save_chats(chat_ids)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat, "Veloxes within {distance} of your route "
+                                                 "will be notified.",
+                                           distance=format_distance(max_distance, chat=chat)))
+
+
+# command to handle /soft_distance
+async def cmd_soft_distance(update: Update,
+                            context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    max_distance = get_pref(chat, "route_distance")
+    try:
+        soft_distance = int(context.args[0])
+        if soft_distance < 0 or 0 < soft_distance <= max_distance:
+            raise ValueError
+    except (IndexError, ValueError):
+        current = get_pref(chat, "soft_route_distance")
+        current = format_distance(current, chat=chat) if current else tr(chat, "none")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /soft_distance <meters>\n"
+                                                     "Must be more than your route distance "
+                                                     "({distance}), 0 disables it.\n\n"
+                                                     "Current soft distance: {current}",
+                                               distance=format_distance(max_distance, chat=chat),
+                                               current=current))
+        return None
+
+    set_pref(chat, "soft_route_distance", soft_distance)
+    save_chats(chat_ids)
+
+    msg = tr(chat, "Disabled - veloxes outside your route are never notified")
+    if soft_distance:
+        msg = tr(chat, "Enabled - once a day you'll hear about veloxes up to {distance} "
+                       "from your route",
+                 distance=format_distance(soft_distance, chat=chat))
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+async def save_home(update, context, chat_ids, home):
+    chat_id = str(update.message.chat_id)
+    chat_ids[chat_id]["home"] = home
+    save_chats(chat_ids)
+
+    chat = chat_ids[chat_id]
+    radius = get_pref(chat, "home_radius")
+    msg = tr(chat, "Home location saved. Use /set_radius to only get veloxes around it.")
+    if radius:
+        msg = tr(chat, "Home location saved - you'll only be notified about veloxes "
+                       "within {distance} of it.",
+                 distance=format_distance(radius, chat=chat))
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /set_home
+async def cmd_set_home(update: Update,
+                       context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    if context.args:
+        home = parse_lat_long(context.args[0])
+        if home is None:
+            await context.bot.send_message(chat_id=update.message.chat_id,
+                                           text=tr(chat, "Usage: /set_home [lat,long]\n"
+                                                         "e.g. /set_home 47.0502,8.3093"))
+            return None
+
+        await save_home(update, context, chat_ids, home)
+        return None
+
+    # the next shared location becomes the home location
+    chat["awaiting_home"] = True
+    save_chats(chat_ids)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat, "Share your home location (📎 > Location) "
+                                                 "and I'll remember it."))
+
+
+# command to handle /set_radius
+async def cmd_set_radius(update: Update,
+                         context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    try:
+        # round() raises OverflowError for inf and ValueError for nan
+        radius = round(float(context.args[0]) * 1000)
+        if radius < 0:
+            raise ValueError
+    except (IndexError, OverflowError, ValueError):
+        current = get_pref(chat, "home_radius")
+        current = format_distance(current, chat=chat) if current else tr(chat, "none")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /set_radius <km>\n"
+                                                     "0 disables the filter.\n\n"
+                                                     "Current radius: {current}",
+                                               current=current))
+        return None
+
+    set_pref(chat, "home_radius", radius)
+    save_chats(chat_ids)
+
+    if not radius:
+        msg = tr(chat, "Disabled - veloxes are notified wherever they are")
+    elif not chat.get("home"):
+        msg = tr(chat, "Radius set to {distance}. Use /set_home to tell me where home is.",
+                 distance=format_distance(radius, chat=chat))
+    else:
+        msg = tr(chat, "Enabled - only veloxes within {distance} of home are notified",
+                 distance=format_distance(radius, chat=chat))
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /filter_area
+async def cmd_filter_area(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    areas = chat.get("areas", [])
+    area = " ".join(context.args).strip()
+
+    if not area:
+        msg = tr(chat, "Usage: /filter_area &lt;municipality&gt; adds or removes "
+                       "a municipality, /filter_area all removes the filter\n\n")
+        if areas:
+            msg += tr(chat, "Only veloxes in these municipalities are notified:\n")
+            msg += bullet_list(sorted(areas, key=collation_key))
+        else:
+            msg += tr(chat, "Veloxes of every municipality are notified.")
+    elif area.casefold() == "all":
+        chat.pop("areas", None)
+        save_chats(chat_ids)
+        msg = tr(chat, "Veloxes of every municipality will be notified.")
+    else:
+        known = {velox_municipality(el) for el in get_saved_dict()}
+        # use the spelling of the website when possible
+        area = next((el for el in known if el.casefold() == area.casefold()), area)
+        matches = [el for el in areas if el.casefold() == area.casefold()]
+        if matches:
+            areas.remove(matches[0])
+            msg = tr(chat, "Removed {area} from your municipalities.",
+                     area=escape(matches[0]))
+        else:
+            areas.append(area)
+            msg = tr(chat, "Added {area} to your municipalities.", area=escape(area))
+            if area not in known:
+                msg += " " + tr(chat, "There is currently no velox listed there.")
+
+        if areas:
+            chat["areas"] = areas
+        else:
+            chat.pop("areas", None)
+            msg += "\n" + tr(chat, "Veloxes of every municipality will be notified.")
+        save_chats(chat_ids)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg, parse_mode=ParseMode.HTML)
+
+
+# command to handle /max_per_hour
+async def cmd_max_per_hour(update: Update,
+                           context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    try:
+        cap = int(context.args[0])
+        if cap < 0:
+            raise ValueError
+    except (IndexError, ValueError):
+        current = get_pref(chat, "max_per_hour")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /max_per_hour <messages>\n"
+                                                     "0 removes the limit.\n\n"
+                                                     "Current limit: {current}",
+                                               current=current or tr(chat, "none")))
+        return None
+
+    set_pref(chat, "max_per_hour", cap)
+    save_chats(chat_ids)
+
+    msg = tr(chat, "Disabled - you'll get every update as it comes")
+    if cap:
+        msg = tr(chat, "Enabled - at most {cap} updates per hour, the rest is summarized "
+                       "in a single message", cap=cap)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /no_updates_every
+async def cmd_no_updates_every(update: Update,
+                               context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    try:
+        hours = int(context.args[0])
+        if hours < 0:
+            raise ValueError
+    except (IndexError, ValueError):
+        current = get_pref(chat, "no_updates_every_h")
+        current = (tr(chat, "{hours} hours", hours=current) if current
+                   else tr(chat, "every check"))
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /no_updates_every <hours>\n"
+                                                     "0 sends one after every check, 24 about "
+                                                     "once a day. Only applies with "
+                                                     "/notify_no_updates enabled.\n\n"
+                                                     "Current interval: {current}",
+                                               current=current))
+        return None
+
+    set_pref(chat, "no_updates_every_h", hours)
+    save_chats(chat_ids)
+
+    msg = tr(chat, "You'll get a \"no changes\" message after every check")
+    if hours:
+        msg = tr(chat, "You'll get at most one \"no changes\" message every {hours} hours",
+                 hours=hours)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /timezone
+async def cmd_timezone(update: Update,
+                       context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    try:
+        timezone = context.args[0]
+        ZoneInfo(timezone)
+    except (IndexError, ValueError, ZoneInfoNotFoundError):
+        current = get_pref(chat, "timezone") or tr(chat, "the bot's server time")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /timezone <name>\n"
+                                                     "e.g. /timezone Europe/Zurich, times in "
+                                                     "your messages are then shown in that "
+                                                     "timezone.\n\n"
+                                                     "Current timezone: {current}",
+                                               current=current))
+        return None
+
+    set_pref(chat, "timezone", timezone)
+    save_chats(chat_ids)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat, "Times are now shown in {timezone}, "
+                                                 "currently {time}",
+                                           timezone=timezone, time=f"{chat_now(chat):%H:%M}"))
+
+
+# command to handle /coordinates
+async def cmd_coordinates(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    if len(context.args) != 1 or context.args[0] not in (*COORDINATE_FORMATS, "off"):
+        current = get_pref(chat, "coordinate_format") or "off"
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /coordinates <format>|off\n")
+                                            + "".join(f"{name}: {tr(chat, description)}\n"
+                                                      for name, description
+                                                      in COORDINATE_FORMATS.items())
+                                            + "\n" + tr(chat, "Current format: {current}",
+                                                         current=current))
+        return None
+
+    coordinate_format = "" if context.args[0] == "off" else context.args[0]
+    set_pref(chat, "coordinate_format", coordinate_format)
+    save_chats(chat_ids)
+
+    msg = tr(chat, "Disabled - veloxes only come as links")
+    if coordinate_format:
+        msg = tr(chat, "Enabled - veloxes come with their coordinates in {format}",
+                 format=coordinate_format)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /units
+async def cmd_units(update: Update,
+                    context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    if len(context.args) != 1 or context.args[0] not in DISTANCE_UNITS:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=tr(chat, "Usage: /units {units}\n\n"
+                                                     "Current units: {current}",
+                                               units="|".join(DISTANCE_UNITS),
+                                               current=get_pref(chat, "distance_unit")))
+        return None
+
+    set_pref(chat, "distance_unit", context.args[0])
+    save_chats(chat_ids)
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=tr(chat, "Distances are now shown in {units} units",
+                                           units=context.args[0]))
+
+
+# command to handle /clear_route
+async def cmd_clear_route(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    chat_id = str(update.message.chat_id)
+    chat_ids = get_chats()
+
+    if not chat_ids:
+        return None
+
+    chat = chat_ids[chat_id]
+    msg = tr(chat, "No route saved. Send me a GPX file or use /commute to set one.")
+    if chat.pop("route", None):
+        chat.pop("commute", None)
+        save_chats(chat_ids)
+        msg = tr(chat, "Route removed - you'll be notified about all veloxes again.")
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+def join_segment(chat, segment):
+    """Add the chat to the segment, False if it already was in it"""
+    if segment in chat.get("segments", []):
+        return False
+
+    chat.setdefault("segments", []).append(segment)
+    return True
+
+
+def get_segment_members(chat_ids, segment):
+    return [chat_id for chat_id, chat in chat_ids.items() if segment in chat.get("segments", [])]
+
+
+def parse_segment_target(text, configs):
+    """Split "@segment rest" into the segment's chat ids and the rest
+
+    The chat ids are None without a leading @segment, e.g. for everyone.
+    Raises ValueError for segments that weren't created.
+    """
+    if not text.startswith("@"):
+        return None, text
+
+    segment, _, rest = text[1:].partition(" ")
+    if segment not in configs.get("SEGMENTS", []):
+        raise ValueError(f"Unknown segment {segment}")
+
+    return get_segment_members(get_chats() or {}, segment), rest.strip()
+
+
+def is_admin(chat_id, context):
+    return str(chat_id) in context.bot_data.get("admin_chat_ids", [])
+
+
+# runs before every other handler, drops updates of banned or flooding chats
+async def check_abuse(update: Update,
+                      context: ContextTypes.DEFAULT_TYPE):
+    # for watch_dispatcher
+    context.bot_data["last_update"] = time.monotonic()
+    # runs first for every update, tag what the handlers log with it
+    enter_span("update", update_id=update.update_id,
+               chat=pseudonymize(update.effective_chat.id) if update.effective_chat else None)
+
+    # inline queries have no chat, they're checked with the user's private chat id
+    sender = update.effective_chat or update.effective_user
+    if not sender or is_admin(sender.id, context):
+        return None
+
+    chat_id = str(sender.id)
+    denylist = get_denylist()
+    entry = denylist.get(chat_id, {})
+    now = time.time()
+
+    if entry.get("banned") or entry.get("throttled_until", 0) > now:
+        raise ApplicationHandlerStop
+
+    if not update.message or not update.message.text or not update.message.text.startswith('/'):
+        return None
+
+    # keep the timestamps of the recent commands of every chat in memory
+    command_times = context.bot_data.setdefault("command_times", {})
+    recent = [t for t in command_times.get(chat_id, []) if t > now - FLOOD_WINDOW_S]
+    recent.append(now)
+    command_times[chat_id] = recent
+
+    if len(recent) <= FLOOD_MAX_COMMANDS:
+        return None
+
+    entry["score"] = entry.get("score", 0) + 1
+    throttle = THROTTLE_S * 2 ** (entry["score"] - 1)
+    entry["throttled_until"] = now + throttle
+    denylist[chat_id] = entry
+    save_denylist(denylist)
+    command_times.pop(chat_id)
+
+    command_log.warning(f"Throttled chat {pseudonymize(chat_id)} for {throttle} s "
+                        f"(abuse score {entry['score']})")
+    audit("system", "throttle",
+          f"{pseudonymize(chat_id)} for {throttle} s, abuse score {entry['score']}")
+    chat = (get_chats() or {}).get(chat_id)
+    language = (get_pref(chat, "language") if chat is not None
+                else user_language(update.effective_user))
+    await context.bot.send_message(chat_id=chat_id,
+                                   text=translate(language, "Too many commands, please try "
+                                                            "again in {minutes} minutes.",
+                                                  minutes=throttle // 60))
+    raise ApplicationHandlerStop
+
+
+# command to handle /admin_ban
+async def cmd_admin_ban(update: Update,
+                        context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    if len(context.args) != 1:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text="Usage: /admin_ban <chat_id>")
+        return None
+
+    banned_id = context.args[0]
+    denylist = get_denylist()
+    denylist.setdefault(banned_id, {})["banned"] = True
+    save_denylist(denylist)
+
+    # banned chats don't get updates anymore either
+    chat_ids = get_chats()
+    if chat_ids and banned_id in chat_ids:
+        chat_ids.pop(banned_id)
+        save_chats(chat_ids)
+        forget_sent_messages([banned_id])
+
+    command_log.info(f"Banned chat {pseudonymize(banned_id)}")
+    audit(pseudonymize(update.message.chat_id), "ban", pseudonymize(banned_id))
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=f"Banned {banned_id}.")
+
+
+# command to handle /admin_restore_backup
+async def cmd_admin_restore_backup(update: Update,
+                                   context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    backups = get_backups()
+    if len(context.args) != 1 or context.args[0] not in backups:
+        msg = "Usage: /admin_restore_backup <backup>\n\n"
+        msg += ("Backups:\n" + "".join(f"- {name}\n" for name in backups) if backups
+                else "No backups yet.")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=msg)
+        return None
+
+    # no check may write the state while it's being replaced
+    async with check_lock:
+        previous = restore_backup(context.args[0])
+
+    # the running bot would otherwise keep, and save back, the replaced configuration
+    configs = context.bot_data["configs"]
+    configs.clear()
+    configs.update(load_configs())
+    apply_overrides(configs)
+    context.bot_data["check_job"].reschedule(trigger=generate_check_trigger(configs))
+
+    audit(pseudonymize(update.message.chat_id), "restore_backup", context.args[0])
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=f"Restored {context.args[0]}. The replaced state "
+                                        f"was backed up as {previous}.")
+
+
+# command to handle /admin_unban
+async def cmd_admin_unban(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    if len(context.args) != 1:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text="Usage: /admin_unban <chat_id>")
+        return None
+
+    denylist = get_denylist()
+    msg = f"{context.args[0]} is neither banned nor throttled."
+    if context.args[0] in denylist:
+        denylist.pop(context.args[0])
+        save_denylist(denylist)
+        command_log.info(f"Unbanned chat {pseudonymize(context.args[0])}")
+        audit(pseudonymize(update.message.chat_id), "unban", pseudonymize(context.args[0]))
+        msg = f"Unbanned {context.args[0]}."
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /admin_simulate
+async def cmd_admin_simulate(update: Update,
+                             context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    # hardcoded coords of Luzern, the same used for map centering
+    added = {"🧪 Simulated velox - test only": ("47.0473835", "8.2532969")}
+
+    to_all = context.args == ["all"]
+    only_chat_ids = None if to_all else [update.message.chat_id]
+    target = "all subscribers" if to_all else "admin chat"
+    if context.args and context.args[0].startswith("@"):
+        try:
+            only_chat_ids, _ = parse_segment_target(context.args[0],
+                                                     context.bot_data.get("configs", {}))
+        except ValueError as e:
+            await context.bot.send_message(chat_id=update.message.chat_id,
+                                           text=str(e))
+            return None
+        target = context.args[0]
+    audit(pseudonymize(update.message.chat_id), "simulate", target)
+
+    await broadcast(context.application, generate_update_msg(added, {}), no_updates=False,
+                    added=added, removed={}, only_chat_ids=only_chat_ids)
+
+
+# command to handle /admin_send_test
+async def cmd_admin_send_test(update: Update,
+                              context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    chat_ids = get_chats() or {}
+    if len(context.args) != 1 or context.args[0] not in chat_ids:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text="Usage: /admin_send_test <subscribed chat_id>")
+        return None
+
+    target_id = context.args[0]
+    chat = chat_ids[target_id]
+
+    # a sample of the saved list as the chat would get it, unlike broadcasts
+    # it ignores the hourly cap, snoozes and pinned lists
+    saved_dict = get_saved_dict()
+    sample = list(filter_velox_for_chat(saved_dict, chat).items())[:2]
+    note = ""
+    if not sample:
+        sample = list(saved_dict.items())[:2]
+        note = "\nIts filters let none of the current veloxes through, the sample is unfiltered."
+    added, removed = dict(sample[:1]), dict(sample[1:])
+
+    msg = (tr(chat, "🧪 Test message, nothing actually changed.") + "\n\n"
+           + generate_update_msg(added, removed, chat))
+    try:
+        await context.bot.send_message(chat_id=target_id, text=msg, parse_mode=ParseMode.HTML,
+                                       disable_web_page_preview=True,
+                                       reply_markup=generate_mute_keyboard(list(added), chat))
+    except TelegramError as e:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=f"Failed to send the test message: {e}")
+        return None
+
+    audit(pseudonymize(update.message.chat_id), "send_test", pseudonymize(target_id))
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=f"Test message sent to {target_id}.{note}")
+
+
+# command to handle /admin_segment
+async def cmd_admin_segment(update: Update,
+                            context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    configs = context.bot_data["configs"]
+    segments = configs.setdefault("SEGMENTS", [])
+    chat_ids = get_chats() or {}
+    action, *args = context.args or [""]
+
+    # names end up in deep link payloads, which only allow these characters
+    if (action in ("create", "delete") and len(args) == 1
+            and re.fullmatch(r"[a-z0-9_]{1,32}", args[0])):
+        segment = args[0]
+        if action == "create" and segment not in segments:
+            segments.append(segment)
+        elif action == "delete" and segment in segments:
+            segments.remove(segment)
+            for chat_id in get_segment_members(chat_ids, segment):
+                chat_ids[chat_id]["segments"].remove(segment)
+            save_chats(chat_ids)
+        save_configs(configs)
+        msg = f"Segment {segment} {action}d."
+    elif (action in ("add", "remove") and len(args) == 2 and args[0] in segments
+          and args[1] in chat_ids):
+        segment, chat_id = args
+        if action == "add":
+            join_segment(chat_ids[chat_id], segment)
+        elif segment in chat_ids[chat_id].get("segments", []):
+            chat_ids[chat_id]["segments"].remove(segment)
+        save_chats(chat_ids)
+        msg = f"{chat_id} {'added to' if action == 'add' else 'removed from'} {segment}."
+    else:
+        msg = ("Usage:\n/admin_segment create|delete <segment>\n"
+               "/admin_segment add|remove <segment> <subscribed chat_id>\n"
+               "Target a segment with /broadcast @segment or /admin_simulate @segment.\n\n")
+        msg += "Segments:\n" if segments else "No segments yet."
+        for segment in segments:
+            msg += (f"- {segment}: {len(get_segment_members(chat_ids, segment))} chats, "
+                    f"join with https://t.me/{context.bot.username}?start=segment_{segment}\n")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=msg, disable_web_page_preview=True)
+        return None
+
+    audit(pseudonymize(update.message.chat_id), f"segment_{action}",
+          " ".join([args[0]] + [pseudonymize(arg) for arg in args[1:]]))
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /admin_stats
+async def cmd_admin_stats(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    configs = context.bot_data["configs"]
+    usage = get_storage_usage()
+    snapshot_days = configs.get("SNAPSHOT_RETENTION_DAYS", DEFAULT_SNAPSHOT_RETENTION_DAYS)
+    message_weeks = configs.get("SENT_MESSAGES_RETENTION_WEEKS",
+                                DEFAULT_SENT_MESSAGES_RETENTION_WEEKS)
+    history_months = configs.get("HISTORY_RETENTION_MONTHS", DEFAULT_HISTORY_RETENTION_MONTHS)
+    audit_months = configs.get("AUDIT_RETENTION_MONTHS", DEFAULT_AUDIT_RETENTION_MONTHS)
+
+    msg = "Stats\n\n"
+    msg += f"Subscribers: {usage['subscribers']}\n"
+    msg += f"Unsubscribed after blocking the bot: {usage['auto_unsubscribed']}\n"
+    msg += (f"Snapshots: {usage['snapshots']} ({usage['snapshots_kb']} kB), "
+            f"kept {snapshot_days} days\n")
+    msg += f"Tracked messages: {usage['tracked_messages']}, kept {message_weeks} weeks\n"
+    msg += (f"History: {usage['history_entries']} veloxes, {usage['changes']} changes, "
+            f"removed ones kept {history_months} months\n")
+    msg += f"Audit entries: {usage['audit_entries']}, kept {audit_months} months\n"
+
+    checks = get_check_entries(since=datetime.now() - timedelta(days=1))
+    failed = [e for e in checks if e["error"]]
+    msg += f"\nChecks in the last 24 h: {len(checks)}, {len(failed)} failed\n"
+    if checks:
+        last = checks[-1]
+        msg += (f"Last check: {last['time']}, "
+                f"{last['error'] or str(last['velox']) + ' veloxes'}, "
+                f"took {last['duration_s']} s\n")
+        changes = sum(len(e["added"]) + len(e["removed"]) for e in checks)
+        msg += f"Changes in the last 24 h: {changes}"
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+# command to handle /broadcast
+async def cmd_broadcast(update: Update,
+                        context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    # keep the line breaks of the announcement, context.args would drop them
+    announcement = update.message.text.partition(" ")[2].strip()
+    configs = context.bot_data.get("configs", {})
+    try:
+        only_chat_ids, text = parse_segment_target(announcement, configs)
+    except ValueError as e:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=str(e))
+        return None
+    if not text:
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text="Usage: /broadcast [@segment] <message>")
+        return None
+
+    audit(pseudonymize(update.message.chat_id), "broadcast", announcement)
+    progress = await broadcast(context.application, f"📢 {escape(text)}", no_updates=False,
+                               only_chat_ids=only_chat_ids, announcement=True)
+    await archive_notification(context.application, f"📢 {escape(text)}")
+
+    msg = f"Announcement sent to {progress['delivered']} chats, {progress['failed']} failed."
+    if progress["cancelled"]:
+        msg += f"\nCancelled before {progress['remaining']} chats."
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+def cancel_broadcast(bot_data):
+    """Make the running broadcast stop before the next chat, False if there's none"""
+    progress = bot_data.get("broadcast_progress")
+    if not progress:
+        return False
+
+    progress["cancelled"] = True
+    return True
+
+
+# handle the cancel button of the broadcast progress message
+async def on_cancel_broadcast_button(update: Update,
+                                     context: ContextTypes.DEFAULT_TYPE):
+    query = update.callback_query
+
+    if not is_admin(query.message.chat_id, context):
+        await query.answer()
+        return None
+
+    if not cancel_broadcast(context.bot_data):
+        await query.answer("No broadcast in progress.")
+        return None
+
+    audit(pseudonymize(query.message.chat_id), "cancel_broadcast")
+    await query.answer("Cancelling the broadcast...")
+
+
+# command to handle /admin_cancel
+async def cmd_admin_cancel(update: Update,
+                           context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    msg = "Nothing to cancel."
+    if cancel_broadcast(context.bot_data):
+        audit(pseudonymize(update.message.chat_id), "cancel_broadcast")
+        msg = "Cancelling the running broadcast, chats already notified keep their update."
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
+
+
+async def on_stop(app):
+    """Stop checking, finish the pending deliveries if they're quick, keep the rest for later"""
+    if "scheduler" in app.bot_data:
+        app.bot_data["scheduler"].shutdown(wait=False)
+
+    deadline = time.monotonic() + SHUTDOWN_TIMEOUT_S
+    # held until exit, no check may start anymore
+    try:
+        await asyncio.wait_for(check_lock.acquire(), timeout=SHUTDOWN_TIMEOUT_S)
+    except asyncio.TimeoutError:
+        state_log.warning("Stopping while a check is still running")
+
+    queue = app.bot_data.get("delivery_queue")
+    if queue is not None:
+        try:
+            await asyncio.wait_for(queue.join(), timeout=max(deadline - time.monotonic(), 0))
+        except asyncio.TimeoutError:
+            state_log.warning(f"Stopping with {queue.qsize()} deliveries queued")
+
+    # let a running broadcast end cleanly, the chats it didn't reach yet miss this update
+    if cancel_broadcast(app.bot_data):
+        deadline = time.monotonic() + SHUTDOWN_TIMEOUT_S
+        while "broadcast_progress" in app.bot_data and time.monotonic() < deadline:
+            await asyncio.sleep(0.1)
+
+    if "delivery_worker" in app.bot_data:
+        app.bot_data["delivery_worker"].cancel()
+    if queue is not None:
+        save_pending_deliveries(queue, app.bot_data.get("held_deliveries", []),
+                                app.bot_data.get("running_delivery"))
+
+
+# command to handle /admin_audit
+async def cmd_admin_audit(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    try:
+        count = int(context.args[0]) if context.args else AUDIT_DEFAULT_ENTRIES
+    except ValueError:
+        count = AUDIT_DEFAULT_ENTRIES
+
+    entries = get_audit_entries(count)
+    msg = "Audit log\n\n"
+    for entry in entries:
+        msg += f"{entry['time']} {entry['actor']}: {entry['action']} {entry['details']}\n"
+    if not entries:
+        msg += "No administrative actions recorded."
+
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg[-MessageLimit.MAX_TEXT_LENGTH:])
+
+
+def parse_check_interval(value):
+    minutes = int(value)
+    # the checks run at fixed minutes of every hour, e.g. 45 would leave gaps of 45 and 15
+    if minutes <= 0 or 60 % minutes:
+        raise ValueError("must divide 60, e.g. 5, 10, 15, 20, 30 or 60")
+    return minutes
+
+
+def parse_hour(value):
+    hour = int(value)
+    if not 0 <= hour <= 23:
+        raise ValueError("must be an hour between 0 and 23")
+    return hour
+
+
+def parse_positive_int(value):
+    number = int(value)
+    if number <= 0:
+        raise ValueError("must be a positive number")
+    return number
+
+
+def parse_bool(value):
+    if value.lower() in ("true", "on", "1"):
+        return True
+    if value.lower() in ("false", "off", "0"):
+        return False
+    raise ValueError("must be true or false")
+
+
+# settings that can be changed at runtime via /admin_config, with their parsers
+EDITABLE_CONFIGS = {
+    "CHECK_INTERVAL_MINUTES": parse_check_interval,
+    "MERGE_UPDATES_DEFAULT": parse_bool,
+    "SNAPSHOT_RETENTION_DAYS": parse_positive_int,
+    "SENT_MESSAGES_RETENTION_WEEKS": parse_positive_int,
+    "HISTORY_RETENTION_MONTHS": parse_positive_int,
+    "AUDIT_RETENTION_MONTHS": parse_positive_int,
+    # no checks from the start hour until the end hour, in the server's time
+    "DOWNTIME_START_HOUR": parse_hour,
+    "DOWNTIME_END_HOUR": parse_hour,
+}
+
+
+def get_downtime_hours(configs):
+    """Length of the daily downtime, 0 if there's none"""
+    start, end = configs.get("DOWNTIME_START_HOUR"), configs.get("DOWNTIME_END_HOUR")
+    if start is None or end is None:
+        return 0
+    return (end - start) % 24
+
 
-def generate_maps_base_url(lat_long_t):
-    return f"https://www.google.com/maps/search/?api=1&query={lat_long_t[0]}%2C{lat_long_t[1]}"
+def generate_check_trigger(configs):
+    minutes = configs.get("CHECK_INTERVAL_MINUTES", DEFAULT_CHECK_INTERVAL_MINUTES)
+    hours = "*"
+    if get_downtime_hours(configs):
+        start = configs["DOWNTIME_START_HOUR"]
+        hours = ",".join(str(hour) for hour in range(24)
+                         if (hour - start) % 24 >= get_downtime_hours(configs))
+    return CronTrigger(
+        year="*", month="*", day="*", hour=hours, minute=f"*/{minutes}", second="0"
+    )
 
 
-def fetch_current_dict():
-    """Fetch the current velox list and returns it as a {location_name:maps_url} dict"""
-    url = 'https://polizei.lu.ch/organisation/sicherheit_verkehrspolizei/verkehrspolizei/spezialversorgung/verkehrssicherheit/Aktuelle_Tempomessungen'
+def describe_check_schedule(configs):
+    minutes = configs.get("CHECK_INTERVAL_MINUTES", DEFAULT_CHECK_INTERVAL_MINUTES)
+    msg = f"Checking every {minutes} minutes"
+    if get_downtime_hours(configs):
+        msg += (f", except from {configs['DOWNTIME_START_HOUR']}:00 "
+                f"to {configs['DOWNTIME_END_HOUR']}:00")
+    return msg
 
-    response = requests.get(url, timeout=30)
-    if response.status_code != 200:
-        print(f"Failed to make request. Status code: {response.status_code}")
-        return None
 
-    soup = BeautifulSoup(response.text, 'html.parser')
-    radar_list_div = soup.find('div', {'id': 'radarList'})
-    if not radar_list_div:
-        print("Could not find div with id 'radarList'")
+# command to handle /admin_config
+async def cmd_admin_config(update: Update,
+                           context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
         return None
 
-    li_tags = radar_list_div.find_all('li')
+    configs = context.bot_data["configs"]
 
-    # exclude the last <li> tag since it is a (rather useless) link to the map itself
-    li_tags = li_tags[:-1]
+    if len(context.args) == 2 and context.args[0].upper() in EDITABLE_CONFIGS:
+        key = context.args[0].upper()
+        try:
+            configs[key] = EDITABLE_CONFIGS[key](context.args[1])
+        except ValueError as e:
+            await context.bot.send_message(chat_id=update.message.chat_id,
+                                           text=f"Invalid value for {key}: {e}")
+            return None
 
-    current_dict = {}
-    for li in li_tags:
-        a_tag = li.find('a')
+        save_configs(configs)
+        audit(pseudonymize(update.message.chat_id), "config", f"{key}={configs[key]}")
+        if key in ("CHECK_INTERVAL_MINUTES", "DOWNTIME_START_HOUR", "DOWNTIME_END_HOUR"):
+            context.bot_data["check_job"].reschedule(trigger=generate_check_trigger(configs))
 
-        # extract and store the text content and coordinates
-        if a_tag:
-            match = re.search(r"map\.flyTo\(\[(.*?),(.*?)\]", a_tag.get('onclick', ''))
-            if match:
-                lat = match.group(1).strip()
-                long = match.group(2).strip()
-                # velox_url = f"https://www.google.com/maps/search/?api=1&query={lat}%2C{long}"
-            else:
-                print(f"Error: couldn't retrieve coordinates for {a_tag.text}")
-                lat = long = None
+    msg = "Configuration\n\n"
+    for key, value in configs.items():
+        # admin chats may be shared, the secrets never show up in them
+        if key.endswith("_TOKEN") or key == "SENTRY_DSN":
+            value = "***"
+        # the environment wins on the next start, changes to these aren't saved
+        source = f" (from VELOX_{key}, not saved)" if key in env_overrides else ""
+        msg += f"{key} = {json.dumps(value)}{source}\n"
+    msg += "\nEditable: " + ", ".join(EDITABLE_CONFIGS)
+    msg += "\nUsage: /admin_config <key> <value>"
 
-            current_dict[a_tag.text] = (lat, long)
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=msg)
 
-    return current_dict
 
+# command to handle /admin_set_interval
+async def cmd_admin_set_interval(update: Update,
+                                 context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
 
-def save_chats(chat_ids):
-    with open(f'{BASE_DIR}/chat_ids.json', 'w', encoding='utf-8') as f:
-        json.dump(chat_ids, f, indent=2)
+    configs = context.bot_data["configs"]
 
+    try:
+        minutes = parse_check_interval(context.args[0])
+        downtime = context.args[1] if len(context.args) > 1 else None
+        if downtime and downtime != "off":
+            start, end = (parse_hour(hour) for hour in downtime.split("-"))
+    except (IndexError, ValueError):
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text="Usage: /admin_set_interval <minutes> "
+                                            "[<from>-<to>|off]\n"
+                                            "e.g. /admin_set_interval 15 1-5 to skip the checks "
+                                            "from 1:00 to 5:00.\n\n"
+                                            f"{describe_check_schedule(configs)}.")
+        return None
 
-# save a new chat_id
-def save_chat_id(chat_id):
-    chat_id = str(chat_id)
+    configs["CHECK_INTERVAL_MINUTES"] = minutes
+    if downtime == "off":
+        configs.pop("DOWNTIME_START_HOUR", None)
+        configs.pop("DOWNTIME_END_HOUR", None)
+    elif downtime:
+        configs["DOWNTIME_START_HOUR"], configs["DOWNTIME_END_HOUR"] = start, end
+    save_configs(configs)
+    context.bot_data["check_job"].reschedule(trigger=generate_check_trigger(configs))
 
-    try:
-        with open(f'{BASE_DIR}/chat_ids.json', 'r', encoding='utf-8') as f:
-            chat_ids = json.load(f)
-    except FileNotFoundError:
-        chat_ids = {}
+    audit(pseudonymize(update.message.chat_id), "set_interval", " ".join(context.args))
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=f"{describe_check_schedule(configs)}.")
 
-    if chat_id in chat_ids.keys():
-        return False
 
-    print(f"New chat id {chat_id}")
-    chat_ids[chat_id] = {"notify_for_no_updates": False}
+# command to handle /admin_quiet
+async def cmd_admin_quiet(update: Update,
+                          context: ContextTypes.DEFAULT_TYPE):
+    if not is_admin(update.message.chat_id, context):
+        return None
+
+    configs = context.bot_data["configs"]
 
-    save_chats(chat_ids)
-    return True
+    if context.args == ["off"]:
+        configs.pop("QUIET_FROM", None)
+        configs.pop("QUIET_UNTIL", None)
+        save_configs(configs)
+        schedule_quiet_release(context.application)
+        held = len(context.bot_data.get("held_deliveries", []))
+        await release_held_deliveries(context.application)
 
+        audit(pseudonymize(update.message.chat_id), "quiet", "off")
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=f"Quiet window ended, {held} held updates queued.")
+        return None
 
-def get_chats():
     try:
-        with open(f'{BASE_DIR}/chat_ids.json', 'r', encoding='utf-8') as f:
-            return json.load(f)
-    except FileNotFoundError:
-        # no previous users
+        minutes = int(context.args[0])
+        if minutes <= 0:
+            raise ValueError
+        start = datetime.now()
+        if len(context.args) > 1:
+            start = datetime.combine(date.today(),
+                                     datetime.strptime(context.args[1], "%H:%M").time())
+            # a time already past today means tomorrow
+            if start < datetime.now():
+                start += timedelta(days=1)
+    except (IndexError, ValueError):
+        msg = ("Usage: /admin_quiet <minutes> [HH:MM] or /admin_quiet off\n"
+               "Updates found meanwhile are held and sent once the window ends, "
+               "checks keep running.\n\n")
+        if configs.get("QUIET_UNTIL"):
+            msg += (f"Quiet window: {configs.get('QUIET_FROM')} to {configs['QUIET_UNTIL']}, "
+                    f"{len(context.bot_data.get('held_deliveries', []))} updates held")
+        else:
+            msg += "No quiet window set."
+        await context.bot.send_message(chat_id=update.message.chat_id,
+                                       text=msg)
         return None
 
+    configs["QUIET_FROM"] = start.isoformat(timespec='seconds')
+    configs["QUIET_UNTIL"] = (start + timedelta(minutes=minutes)).isoformat(timespec='seconds')
+    save_configs(configs)
+    schedule_quiet_release(context.application)
 
-def should_notify_no_updates(chat_id):
-    chat_id = str(chat_id)
-    chat_ids = get_chats()
+    audit(pseudonymize(update.message.chat_id), "quiet",
+          f"{configs['QUIET_FROM']} to {configs['QUIET_UNTIL']}")
+    await context.bot.send_message(chat_id=update.message.chat_id,
+                                   text=f"Updates will be held from {start:%d.%m.%Y %H:%M} "
+                                        f"for {minutes} minutes.")
 
-    if not chat_ids:
-        return False
 
-    return chat_ids[chat_id].get("notify_for_no_updates", False)
+async def deliver_update(app, msg, no_updates, added, removed, current_dict):
+    """Send the update of a check and bring the earlier messages up to date"""
+    cancelled = (await broadcast(app, msg, no_updates=no_updates, added=added,
+                                 removed=removed))["cancelled"]
+    if added or removed:
+        await archive_notification(app, msg)
+    await send_held_back_summaries(app)
+    # the list is saved anyway, otherwise the next check would notify everyone again
+    if removed and not cancelled:
+        await strike_removed_velox(app, removed)
+    if (added or removed) and not cancelled:
+        await update_pinned_lists(app, current_dict)
 
 
-async def broadcast(app, msg, no_updates):
-    chat_ids = get_chats()
+async def deliver_failure(app, msg):
+    await broadcast(app, msg, no_updates=False, category=Category.FAILURES)
 
-    if not chat_ids:
-        return None
 
-    for chat_id in chat_ids.keys():
-        chat_id = str(chat_id)
-        if no_updates and not should_notify_no_updates(chat_id):
-            continue
-        await app.bot.send_message(chat_id=chat_id, text=msg,
-                                   parse_mode=ParseMode.HTML,
-                                   disable_web_page_preview=True)
+def is_quiet(configs):
+    """Whether a quiet window set with /admin_quiet is holding the deliveries"""
+    now = datetime.now().isoformat(timespec='seconds')
+    return configs.get("QUIET_FROM", "") <= now < configs.get("QUIET_UNTIL", "")
 
 
-# command to handle /start
-async def cmd_start(update: Update,
-                    context: ContextTypes.DEFAULT_TYPE):
-    chat_id = update.message.chat_id
-    newly_subscribed = save_chat_id(chat_id)
-    msg = "You're subscribed to updates."
-    if not newly_subscribed:
-        msg = "Already subscribed."
-    await context.bot.send_message(chat_id=chat_id,
-                                   text=msg)
+def schedule_quiet_release(app):
+    """Release the held deliveries when the quiet window ends"""
+    scheduler = app.bot_data["scheduler"]
+    if scheduler.get_job("release_held_deliveries"):
+        scheduler.remove_job("release_held_deliveries")
 
+    until = app.bot_data["configs"].get("QUIET_UNTIL")
+    if until and until > datetime.now().isoformat(timespec='seconds'):
+        scheduler.add_job(
+            release_held_deliveries,
+            trigger="date",
+            run_date=datetime.fromisoformat(until),
+            args=[app],
+            id="release_held_deliveries",
+            name="release_held_deliveries",
+        )
 
-# command to handle /current_list
-async def cmd_current_list(update: Update,
-                           context: ContextTypes.DEFAULT_TYPE):
 
-    msg = "Current List\n\n"
-    for velox, lat_long_t in fetch_current_dict().items():
-        msg += f"- <a href='{generate_maps_base_url(lat_long_t)}'>{velox}</a>\n"
+async def release_held_deliveries(app):
+    # the window was moved or extended meanwhile
+    if is_quiet(app.bot_data.get("configs", {})):
+        return
 
-    await context.bot.send_message(chat_id=update.message.chat_id,
-                                   text=msg, parse_mode=ParseMode.HTML,
-                                   disable_web_page_preview=True)
+    held = app.bot_data.pop("held_deliveries", [])
+    if held:
+        notify_log.info(f"Delivering {len(held)} held updates")
+    for deliver, kwargs in held:
+        await enqueue_delivery(app, deliver, **kwargs)
 
 
-# command to handle /show_map
-async def cmd_show_map(update: Update,
-                       context: ContextTypes.DEFAULT_TYPE):
+async def enqueue_delivery(app, deliver, **kwargs):
+    """Hand a delivery to the delivery worker, or run it right away if there's none"""
+    if is_quiet(app.bot_data.get("configs", {})):
+        # a "no changes" message would be outdated by the end of the window
+        if not kwargs.get("no_updates"):
+            app.bot_data.setdefault("held_deliveries", []).append((deliver, kwargs))
+            notify_log.info("Quiet window, holding the delivery")
+        return
 
-    url = "https://www.google.com/maps/dir/"
-    # hardcoded coords of Luzern for map centering
-    url_suffix = "//@47.0473835,8.2532969,12.25z"
+    queue = app.bot_data.get("delivery_queue")
+    if queue is None:
+        await deliver(app, **kwargs)
+        return
 
-    for _, lat_long_t in fetch_current_dict().items():
-        url += f"{lat_long_t[0]},{lat_long_t[1]}/"
-    url += url_suffix
+    # backpressure, checks only slow down when deliveries can't keep up at all
+    if queue.full():
+        notify_log.warning("Delivery queue full, waiting for the delivery worker")
+    await queue.put((deliver, kwargs))
 
-    msg = f"Velox map\n{url}"
 
-    await context.bot.send_message(chat_id=update.message.chat_id,
-                                   text=msg, parse_mode=ParseMode.HTML,
-                                   disable_web_page_preview=True)
+async def delivery_worker(app):
+    """Run the queued deliveries one after the other, so checks never wait for them"""
+    queue = app.bot_data["delivery_queue"]
+    while True:
+        deliver, kwargs = await queue.get()
+        app.bot_data["running_delivery"] = (deliver, kwargs)
+        try:
+            with span("delivery"):
+                await deliver(app, **kwargs)
+        except Exception:
+            notify_log.exception("Delivery failed")
+        finally:
+            queue.task_done()
+        # kept when cancelled by on_stop, which saves it
+        app.bot_data.pop("running_delivery")
 
 
-# command to handle /manual_update
-async def cmd_manual_update(_update: Update,
-                            context: ContextTypes.DEFAULT_TYPE):
-    return await check_for_updates(context.application, forced_update=True)
+# deliveries that can be kept across restarts, by name
+DELIVERIES = {deliver.__name__: deliver for deliver in (deliver_update, deliver_failure)}
 
 
-# command to handle /notify_no_updates
-async def cmd_set_notify_no_updates(update: Update,
-                                    context: ContextTypes.DEFAULT_TYPE):
-    chat_id = str(update.message.chat_id)
-    chat_ids = get_chats()
+def save_pending_deliveries(queue, held=(), running=None):
+    """Keep the deliveries that never started or were interrupted for the next start"""
+    # the interrupted one starts over, it was the first in line
+    pending = [{"delivery": running[0].__name__, "kwargs": running[1]}] if running else []
+    while not queue.empty():
+        deliver, kwargs = queue.get_nowait()
+        pending.append({"delivery": deliver.__name__, "kwargs": kwargs})
+    pending += [{"delivery": deliver.__name__, "kwargs": kwargs} for deliver, kwargs in held]
 
-    if not chat_ids:
-        return None
+    if pending:
+        with atomic_open(f'{STATE_DIR}/pending_deliveries.json', backup=False) as f:
+            json.dump(pending, f, ensure_ascii=False)
+        state_log.info(f"Saved {len(pending)} pending deliveries for the next start")
 
-    new_val = not chat_ids[chat_id]["notify_for_no_updates"]
-    chat_ids[chat_id]["notify_for_no_updates"] = new_val
 
-    save_chats(chat_ids)
+def load_pending_deliveries():
+    """The deliveries saved when the bot last stopped, removed from disk"""
+    try:
+        with open(f'{STATE_DIR}/pending_deliveries.json', 'r', encoding='utf-8') as f:
+            pending = json.load(f)
+    except (FileNotFoundError, ValueError):
+        return []
 
-    msg = "Disabled - no status updates if no changes are detected"
-    if new_val:
-        msg = "Enabled - get status updates even if no changes are detected"
+    os.remove(f'{STATE_DIR}/pending_deliveries.json')
+    return [(DELIVERIES[p["delivery"]], p["kwargs"]) for p in pending]
+
+
+async def on_start(app):
+    app.bot_data["delivery_queue"] = asyncio.Queue(maxsize=DELIVERY_QUEUE_SIZE)
+    app.bot_data["last_update"] = time.monotonic()
+    # not app.create_task, the application would wait for it forever when stopping
+    app.bot_data["delivery_worker"] = asyncio.create_task(delivery_worker(app))
+
+    # more than fit in the queue if a quiet window was holding them
+    app.bot_data["held_deliveries"] = load_pending_deliveries()
+    if not is_quiet(app.bot_data.get("configs", {})):
+        asyncio.create_task(release_held_deliveries(app))
+
+
+# circuit breaker around the police website, "opened" is set while it's considered down
+scrape_circuit = {"failures": 0, "opened": None, "last_attempt": 0}
+
+
+def circuit_allows_fetch():
+    """Always while the circuit is closed, once every CIRCUIT_PROBE_S while it's open"""
+    return (scrape_circuit["opened"] is None
+            or time.monotonic() - scrape_circuit["last_attempt"] >= CIRCUIT_PROBE_S)
+
+
+async def record_fetch_result(app, ok):
+    """Open the circuit after too many failures in a row, close it on the first success"""
+    scrape_circuit["last_attempt"] = time.monotonic()
+
+    if ok:
+        scrape_circuit["failures"] = 0
+        if scrape_circuit["opened"] is not None:
+            scrape_circuit["opened"] = None
+            scrape_log.info("The police website recovered, closing the circuit")
+            if app:
+                await notify_admins(app, "The police website is back, checks run normally again.")
+        return
+
+    scrape_circuit["failures"] += 1
+    if scrape_circuit["opened"] is None and scrape_circuit["failures"] >= CIRCUIT_MAX_FAILURES:
+        scrape_circuit["opened"] = datetime.now().isoformat(timespec='seconds')
+        scrape_log.error(f"{scrape_circuit['failures']} failed checks in a row, opening the circuit")
+        if app:
+            await notify_admins(app, f"The police website failed {scrape_circuit['failures']} "
+                                     f"checks in a row. Checks are paused and it's probed every "
+                                     f"{CIRCUIT_PROBE_S // 60} minutes until it's back.")
+
+
+# a check started while another one is running is skipped, they'd race on the state files
+check_lock = asyncio.Lock()
 
-    await context.bot.send_message(chat_id=update.message.chat_id,
-                                   text=f"{msg}")
 
+async def check_for_updates(app=None, save_list=True, forced_update=False, trigger="scheduled"):
+    """Check for changes and send updates to registered users, unless a check is running"""
+    if check_lock.locked():
+        scrape_log.info(f"Skipping the {trigger} check, another check is running")
+        return
+
+    async with check_lock:
+        with span("check", trigger=trigger):
+            await run_check(app, save_list, forced_update, trigger)
+
+
+async def run_check(app, save_list, forced_update, trigger):
+    started = time.monotonic()
 
-async def check_for_updates(app=None, save_list=True, forced_update=False):
-    """Check for changes and send updates to registered users"""
+    # manual updates always try, e.g. to see if the website is back
+    if not forced_update and not circuit_allows_fetch():
+        scrape_log.info("Circuit open, skipping the check")
+        log_check(started, trigger, None, error="circuit open")
+        return
 
     # fetch the current list
-    current_dict = fetch_current_dict()
+    html = await fetch_page()
+    page_hash = radar_list_hash(html) if html is not None else None
+    # nothing to parse or diff if the list is the same HTML as last time
+    page_unchanged = page_hash is not None and page_hash == last_page["hash"]
+    if page_unchanged:
+        current_dict = get_saved_dict()
+    else:
+        current_dict = None
+        if html is not None:
+            current_dict = await asyncio.to_thread(parse_velox_list, html)
     no_updates = False
 
+    # users were told about the failures until the circuit opened, no need to repeat it
+    was_open = scrape_circuit["opened"] is not None
+    await record_fetch_result(app, current_dict is not None)
+
     if current_dict is None:
         msg = "Failed to fetch updates."
 
-        print(msg)
-        if app:
-            await broadcast(app, msg, no_updates=no_updates)
+        scrape_log.error(msg)
+        log_check(started, trigger, None, error="fetch failed" if html is None else "parse failed")
+        if app and not was_open:
+            await enqueue_delivery(app, deliver_failure, msg=msg)
 
         return
 
-    set_current = set(current_dict.keys())
-
-    # load previous dict
-    try:
-        with open(f'{BASE_DIR}/previous_dict.json', 'r', encoding='utf-8') as f:
-            previous_dict = json.load(f)
-            set_previous = set(previous_dict.keys())
-    except (FileNotFoundError, ValueError):
-        set_previous = set()
-
-    # compare and find changes
-    added = set_current - set_previous
-    removed = set_previous - set_current
+    # load previous dict and find changes
+    previous_dict = get_saved_dict()
+    if page_unchanged:
+        added, removed = {}, {}
+        scrape_log.debug("Velox list unchanged since the last check")
+    else:
+        added, removed = diff_velox(previous_dict, current_dict)
 
     # generate the message to send
-    msg = "Checking for updates\n\n"
-    if added:
-        msg += "Added:\n"
-        for el in added:
-            msg += f"- <a href='{generate_maps_base_url(current_dict[el])}'>{el}</a>\n"
-    if removed:
-        msg += "Removed:\n"
-        for el in removed:
-            msg += f"- <a href='{generate_maps_base_url(previous_dict[el])}'>{el}</a>\n"
+    msg = generate_update_msg(added, removed)
     if not added and not removed:
-        msg += "No changes detected."
         # mask no_updates flag if forced_update
         no_updates = not forced_update
 
-    print(msg)
+    if not page_unchanged:
+        notify_log.info(msg)
     if app:
-        await broadcast(app, msg, no_updates=no_updates)
+        await enqueue_delivery(app, deliver_update, msg=msg, no_updates=no_updates,
+                               added=added, removed=removed, current_dict=current_dict)
 
     if not no_updates and save_list:
         # save the current list
-        with open(f'{BASE_DIR}/previous_dict.json', 'w', encoding='utf-8') as f:
-            json.dump(current_dict, f)
+        save_saved_dict(current_dict)
+
+        # keep the page around to replay it when changing the parser
+        if added or removed:
+            save_snapshot(html)
+
+    if save_list:
+        last_page["hash"] = page_hash
+        update_velox_history(previous_dict, current_dict, added, removed)
+        log_changes(previous_dict, added, removed)
+        if added or removed or not os.path.exists(f'{STATE_DIR}/poi/veloxes.gpx'):
+            save_poi_exports(current_dict)
+
+    log_check(started, trigger, current_dict, added, removed)
+
+
+def export_chats(path):
+    chat_ids = get_chats() or {}
+    with open(path, 'w', encoding='utf-8') as f:
+        json.dump({"format": "velox-chats", "version": 1, "chats": chat_ids}, f, indent=2)
+
+    print(f"Exported {len(chat_ids)} chats to {path}")
+
+
+def import_chats(path):
+    """Merge exported chats into the local ones, local preferences win"""
+    with open(path, 'r', encoding='utf-8') as f:
+        exported = json.load(f)
+    # also accept a plain chat_ids.json
+    imported = exported.get("chats", {}) if exported.get("format") == "velox-chats" else exported
+
+    chat_ids = get_chats() or {}
+    new_chats = 0
+    for chat_id, chat in imported.items():
+        if chat_id not in chat_ids:
+            new_chats += 1
+        local = chat_ids.get(chat_id, {})
+        chat_ids[chat_id] = {**chat, **local,
+                             "preferences": {**chat.get("preferences", {}),
+                                             **local.get("preferences", {})}}
+
+    save_chats(chat_ids)
+    audit("cli", "import", f"{new_chats} new chats from {path}")
+    print(f"Imported {len(imported)} chats from {path}, {new_chats} of them new")
+
+
+def migrate_velox_names():
+    """Re-key the stored state with normalized velox names, a no-op once done"""
+    saved_dict = get_saved_dict()
+    normalized = {normalize_velox_name(el): lat_long_t for el, lat_long_t in saved_dict.items()}
+    if list(normalized) != list(saved_dict):
+        save_saved_dict(normalized)
+        state_log.info("Normalized velox names of the saved list")
+
+    chat_ids = get_chats()
+    if chat_ids:
+        changed = False
+        for chat in chat_ids.values():
+            for key in ("followed", "muted"):
+                if key in chat:
+                    names = list(dict.fromkeys(normalize_velox_name(el) for el in chat[key]))
+                    changed |= names != chat[key]
+                    chat[key] = names
+        if changed:
+            save_chats(chat_ids)
+            state_log.info("Normalized velox names of followed and muted veloxes")
+
+    sent_messages = get_sent_messages()
+    changed = False
+    for chat_messages in sent_messages.values():
+        for tracked in chat_messages:
+            for key in ("velox", "buttons"):
+                names = [normalize_velox_name(el) for el in tracked.get(key, [])]
+                changed |= names != tracked.get(key, [])
+                tracked[key] = names
+            # strike_removed_velox() looks the names up in the message text
+            text = re.sub(r"(<a href='[^']*'>)([^<]*)(</a>)",
+                          lambda m: m.group(1) + normalize_velox_name(m.group(2)) + m.group(3),
+                          tracked["text"])
+            changed |= text != tracked["text"]
+            tracked["text"] = text
+    if changed:
+        save_sent_messages(sent_messages)
+        state_log.info("Normalized velox names of tracked messages")
+
+
+def migrate_preferences():
+    """Move preferences stored as top-level chat keys into the preferences map"""
+    chat_ids = get_chats()
+    if not chat_ids:
+        return
+
+    changed = False
+    for chat in chat_ids.values():
+        for key in PREFERENCES:
+            if key in chat:
+                chat.setdefault("preferences", {})[key] = chat.pop(key)
+                changed = True
+
+    if changed:
+        save_chats(chat_ids)
+        state_log.info("Moved chat preferences into the preferences map")
+
+
+def generate_new_preferences_msg(chat):
+    """Notice of the toggles added since the chat last heard about settings, None if none"""
+    new = [pref for pref in PREFERENCES.values()
+           if pref.label and pref.version > chat.get("preferences_version", 1)]
+    if not new:
+        return None
+
+    msg = ""
+    for key, pref in PREFERENCES.items():
+        if pref in new:
+            msg += tr(chat, "New setting available: {label} (currently {state})",
+                      label=tr(chat, pref.label),
+                      state=tr(chat, "on" if get_pref(chat, key) else "off")) + "\n"
+    return msg + tr(chat, "Change it in /settings.")
+
+
+async def announce_new_preferences(app):
+    """Tell every chat once about the toggles added since it subscribed or was last told"""
+    chat_ids = get_chats()
+    if not chat_ids:
+        return
+
+    changed = False
+    for chat_id, chat in chat_ids.items():
+        if chat.get("preferences_version", 1) >= PREFERENCES_VERSION:
+            continue
+
+        msg = generate_new_preferences_msg(chat)
+        if msg:
+            try:
+                await send_with_retry(app.bot.send_message, chat_id=chat_id, text=msg)
+            except TelegramError as e:
+                notify_log.warning(f"Failed to announce new settings "
+                                   f"to chat {pseudonymize(chat_id)}: {e}")
+                continue
+        chat["preferences_version"] = PREFERENCES_VERSION
+        changed = True
+
+    if changed:
+        save_chats(chat_ids)
+
+
+def save_snapshot(html):
+    os.makedirs(f'{STATE_DIR}/snapshots', exist_ok=True)
+    with open(f'{STATE_DIR}/snapshots/{datetime.now():%Y%m%d-%H%M%S}.html', 'w',
+              encoding='utf-8') as f:
+        f.write(html)
+
+
+def purge_log(filename, oldest, before_rewrite=None):
+    """Drop the entries of a JSON lines log older than the oldest datetime, return how many
+
+    before_rewrite is called with that number before the log is rewritten.
+    """
+    try:
+        with open(f'{STATE_DIR}/{filename}', 'r', encoding='utf-8') as f:
+            lines = f.readlines()
+    except FileNotFoundError:
+        return 0
+
+    # entries are appended in time order
+    oldest = oldest.isoformat(timespec='seconds')
+    purged = next((i for i, line in enumerate(lines) if json.loads(line)["time"] >= oldest),
+                  len(lines))
+    if purged:
+        if before_rewrite:
+            before_rewrite(purged)
+        with atomic_open(f'{STATE_DIR}/{filename}') as f:
+            f.writelines(lines[purged:])
+    return purged
+
+
+def add_changes_purged(count):
+    with atomic_open(f'{STATE_DIR}/changes_purged', backup=False) as f:
+        f.write(str(get_changes_purged() + count))
+
+
+async def purge_old_data(app):
+    """Purge the data past its retention once the running check and deliveries are done
+
+    They rewrite the same files. The purge itself never awaits, so handlers can't
+    interleave either.
+    """
+    async with check_lock:
+        queue = app.bot_data.get("delivery_queue")
+        if queue is not None:
+            await queue.join()
+        purge_expired(app.bot_data["configs"])
+
+
+def purge_expired(configs):
+    """Delete snapshots, tracked messages, history and log entries older than their retention"""
+    snapshot_days = configs.get("SNAPSHOT_RETENTION_DAYS", DEFAULT_SNAPSHOT_RETENTION_DAYS)
+    message_weeks = configs.get("SENT_MESSAGES_RETENTION_WEEKS",
+                                DEFAULT_SENT_MESSAGES_RETENTION_WEEKS)
+    history_months = configs.get("HISTORY_RETENTION_MONTHS", DEFAULT_HISTORY_RETENTION_MONTHS)
+    audit_months = configs.get("AUDIT_RETENTION_MONTHS", DEFAULT_AUDIT_RETENTION_MONTHS)
+
+    purged_snapshots = 0
+    if os.path.isdir(f'{STATE_DIR}/snapshots'):
+        oldest = time.time() - snapshot_days * 24 * 3600
+        for filename in os.listdir(f'{STATE_DIR}/snapshots'):
+            path = f'{STATE_DIR}/snapshots/{filename}'
+            if os.path.getmtime(path) < oldest:
+                os.remove(path)
+                purged_snapshots += 1
+
+    purged_messages = 0
+    oldest_date = (date.today() - timedelta(weeks=message_weeks)).isoformat()
+    sent_messages = get_sent_messages()
+    for chat_id, chat_messages in sent_messages.items():
+        kept = [m for m in chat_messages if m.get("date", oldest_date) > oldest_date]
+        purged_messages += len(chat_messages) - len(kept)
+        sent_messages[chat_id] = kept
+    save_sent_messages(sent_messages)
+
+    # the check log is kept as long as the snapshots
+    purged_checks = purge_log('checks.jsonl', datetime.now() - timedelta(days=snapshot_days))
+
+    # veloxes still listed stay in the history whenever they were first seen
+    oldest_history = datetime.now() - timedelta(days=30 * history_months)
+    history = get_velox_history()
+    kept = {el: entry for el, entry in history.items()
+            if entry.get("removed", oldest_history.isoformat()) >= oldest_history.isoformat()}
+    if len(kept) < len(history):
+        save_velox_history(kept)
+
+    # the cursors of the changes feed count the purged lines, see get_changes_since
+    with changes_lock:
+        purged_changes = purge_log('changes.jsonl', oldest_history,
+                                   before_rewrite=add_changes_purged)
+
+    purged_audit = purge_log('audit.jsonl', datetime.now() - timedelta(days=30 * audit_months))
+
+    state_log.info(f"Purged {purged_snapshots} snapshots, {purged_messages} tracked messages, "
+                   f"{purged_checks} check log entries, {len(history) - len(kept)} removed "
+                   f"veloxes, {purged_changes} changes and {purged_audit} audit entries")
+
+
+def get_backups():
+    """Names of the backups, oldest first"""
+    if not os.path.isdir(f'{STATE_DIR}/backups'):
+        return []
+    return sorted(os.listdir(f'{STATE_DIR}/backups'))
+
+
+def backup_state(keep=None):
+    """Copy the state files into a new backup, dropping the ones past the retention but keep"""
+    name = f"{datetime.now():%Y%m%d-%H%M%S}"
+    os.makedirs(f'{STATE_DIR}/backups/{name}')
+    for filename in BACKUP_FILES:
+        if os.path.exists(f'{STATE_DIR}/{filename}'):
+            shutil.copy2(f'{STATE_DIR}/{filename}', f'{STATE_DIR}/backups/{name}/{filename}')
+
+    for old in get_backups()[:-BACKUP_RETENTION]:
+        if old != keep:
+            shutil.rmtree(f'{STATE_DIR}/backups/{old}')
+
+    state_log.info(f"Backed up the state to backups/{name}")
+    return name
+
+
+def restore_backup(name):
+    """Put the files of the backup back in place, returning the backup of the replaced state"""
+    # the state being replaced is backed up too, so that a restore can be undone
+    previous = backup_state(keep=name)
+    # the restored list may not match the page anymore
+    last_page["hash"] = None
+    for filename in os.listdir(f'{STATE_DIR}/backups/{name}'):
+        shutil.copy2(f'{STATE_DIR}/backups/{name}/{filename}', f'{STATE_DIR}/{filename}.tmp')
+        os.replace(f'{STATE_DIR}/{filename}.tmp', f'{STATE_DIR}/{filename}')
+
+    state_log.info(f"Restored the state from backups/{name}")
+    return previous
+
+
+def get_storage_usage():
+    snapshots = []
+    if os.path.isdir(f'{STATE_DIR}/snapshots'):
+        snapshots = [f'{STATE_DIR}/snapshots/{f}' for f in os.listdir(f'{STATE_DIR}/snapshots')]
+
+    return {
+        "subscribers": len(get_chats() or {}),
+        "snapshots": len(snapshots),
+        "snapshots_kb": sum(os.path.getsize(f) for f in snapshots) // 1024,
+        "tracked_messages": sum(len(m) for m in get_sent_messages().values()),
+        "history_entries": len(get_velox_history()),
+        "changes": len(get_recent_changes(sys.maxsize)),
+        "audit_entries": len(get_audit_entries(sys.maxsize)),
+        "auto_unsubscribed": len([e for e in get_audit_entries(sys.maxsize)
+                                  if e["action"] == "auto_unsubscribe"]),
+    }
+
+
+def replay_snapshots(directory):
+    """Run stored pages through parsing and diffing in order, without notifying anyone"""
+    previous_dict = {}
+    for filename in sorted(os.listdir(directory)):
+        if not filename.endswith('.html'):
+            continue
+
+        with open(os.path.join(directory, filename), 'r', encoding='utf-8') as f:
+            current_dict = parse_velox_list(f.read(), geocode=False)
+
+        print(f"=== {filename}")
+        if current_dict is None:
+            print("Failed to parse the page.\n")
+            continue
+
+        added, removed = diff_velox(previous_dict, current_dict)
+        print(f"{len(current_dict)} veloxes")
+        print(generate_update_msg(added, removed) + "\n")
+        previous_dict = current_dict
+
+
+# files served to navigation units at /poi/<name>?token=<POI_TOKEN>
+POI_EXPORTS = {
+    "veloxes.gpx": ("application/gpx+xml", export.to_gpx),
+    "veloxes.csv": ("text/csv", export.to_csv),
+}
+
+
+def save_poi_exports(current_dict):
+    """Regenerate the POI files polled by navigation units"""
+    os.makedirs(f'{STATE_DIR}/poi', exist_ok=True)
+    for name, (_, to_format) in POI_EXPORTS.items():
+        with atomic_open(f'{STATE_DIR}/poi/{name}', backup=False) as f:
+            f.write(to_format(sort_by_name(current_dict)))
+
+
+def get_health(app):
+    """Last successful check, polling and state dir status, with an overall ok"""
+    last_success = next((e["time"] for e in reversed(get_check_entries()) if not e["error"]),
+                        None)
+
+    try:
+        with open(f'{STATE_DIR}/.healthz', 'w', encoding='utf-8') as f:
+            f.write(datetime.now().isoformat(timespec='seconds'))
+        writable = True
+    except OSError:
+        writable = False
+
+    configs = app.bot_data["configs"]
+    interval = configs.get("CHECK_INTERVAL_MINUTES", DEFAULT_CHECK_INTERVAL_MINUTES)
+    # no checks during the downtime isn't a problem
+    stale_before = datetime.now() - timedelta(minutes=interval * HEALTH_MAX_MISSED_CHECKS,
+                                              hours=get_downtime_hours(configs))
+    polling = bool(app.running and app.updater and app.updater.running)
+
+    return {"ok": bool(last_success and datetime.fromisoformat(last_success) >= stale_before
+                       and polling and writable),
+            "last_successful_check": last_success,
+            "polling": polling,
+            "storage_writable": writable,
+            # informative only, serving the cached page is what it's there for
+            "stale_page_fetched_at": stale_page["fetched_at"]}
+
+
+async def watch_dispatcher(app):
+    """Restart polling when it stopped or got no update for too long
+
+    e.g. after a network partition left the long polling hanging.
+    """
+    silent_s = time.monotonic() - app.bot_data.get("last_update", 0)
+    polling = app.updater.running
+    # the updater stops first when shutting down
+    if not app.running or (polling and silent_s < DISPATCHER_SILENCE_S):
+        return
+
+    reason = f"no update for {silent_s:.0f} s" if polling else "it stopped"
+    state_log.warning(f"Restarting polling, {reason}")
+    app.bot_data["last_update"] = time.monotonic()
+    try:
+        if app.updater.running:
+            await app.updater.stop()
+        await app.updater.start_polling()
+    except TelegramError as e:
+        state_log.error(f"Failed to restart polling: {e}")
+        await notify_admins(app, f"⚠️ Polling failed and couldn't be restarted: {e}")
+        return
+
+    # a silent bot may just be idle, a stopped updater is worth telling
+    if not polling:
+        await notify_admins(app, "⚠️ Polling had stopped, it has been restarted.")
 
 
 def bot_start():
     # get the token from config.json
     try:
-        with open(f'{BASE_DIR}/config.json', 'r', encoding='utf-8') as f:
+        with open(f'{STATE_DIR}/config.json', 'r', encoding='utf-8') as f:
             configs = json.load(f)
     except (FileNotFoundError, ValueError):
-        with open(f'{BASE_DIR}/config.json', 'w', encoding='utf-8') as f:
-            configs = {"BOT_TOKEN": ""}
-            json.dump(configs, f, indent=2)
+        configs = {"BOT_TOKEN": "", "ADMIN_CHAT_IDS": []}
+        save_configs(configs)
 
-        print("Error: no valid config.json found. A template has been created,"
+        print(f"Error: no valid {STATE_DIR}/config.json found. A template has been created,"
               "but you need to fill your bot's token.")
         sys.exit(1)
 
     if configs["BOT_TOKEN"] in (None, ""):
-        print("Error: no BOT_TOKEN in config.json. Please add it.")
+        print(f"Error: no BOT_TOKEN in {STATE_DIR}/config.json. Please add it.")
         sys.exit(1)
 
-    app = ApplicationBuilder().token(configs["BOT_TOKEN"]).build()
+    # e.g. VELOX_CHECK_INTERVAL_MINUTES=10 in the service environment, wins over config.json
+    apply_overrides(configs)
+
+    builder = (ApplicationBuilder().token(configs["BOT_TOKEN"])
+               .post_init(on_start).post_stop(on_stop)
+               # stays below the Telegram limits of about 30 messages per second overall
+               # and 20 per minute in a group, for every request of the bot
+               .rate_limiter(AIORateLimiter(overall_max_rate=BROADCAST_MAX_PER_S)))
+    # e.g. a local telegram-bot-api server or a test double
+    if configs.get("API_URL"):
+        builder = builder.base_url(configs["API_URL"])
+    if configs.get("API_FILE_URL"):
+        builder = builder.base_file_url(configs["API_FILE_URL"])
+    # a local server hands out files as paths on disk and lifts the 20 MB download limit
+    if configs.get("API_LOCAL_MODE"):
+        builder = builder.local_mode(True)
+    app = builder.build()
+    app.bot_data["configs"] = configs
+    app.bot_data["admin_chat_ids"] = [str(x) for x in configs.get("ADMIN_CHAT_IDS", [])]
+    # e.g. ADMIN_CHAT_IDS=123,456 in the service environment
+    if os.environ.get("ADMIN_CHAT_IDS"):
+        app.bot_data["admin_chat_ids"] = [x.strip() for x in
+                                          os.environ["ADMIN_CHAT_IDS"].split(",") if x.strip()]
+
+    app.add_handler(TypeHandler(Update, check_abuse), group=-1)
 
     app.add_handler(CommandHandler("start",
                                    cmd_start))
     app.add_handler(CommandHandler("current_list",
                                    cmd_current_list))
+    app.add_handler(CommandHandler("history",
+                                   cmd_history))
+    app.add_handler(CommandHandler("export_geojson",
+                                   cmd_export_geojson))
     app.add_handler(CommandHandler("manual_update",
                                    cmd_manual_update))
-    app.add_handler(CommandHandler("notify_no_updates",
-                                   cmd_set_notify_no_updates))
     app.add_handler(CommandHandler("show_map",
                                    cmd_show_map))
+    app.add_handler(CommandHandler("pin_list",
+                                   cmd_set_pin_list))
+    app.add_handler(CommandHandler("follow",
+                                   cmd_follow))
+    app.add_handler(CommandHandler("unfollow",
+                                   cmd_unfollow))
+    app.add_handler(CommandHandler("unmute",
+                                   cmd_unmute))
+    app.add_handler(CommandHandler("commute",
+                                   cmd_commute))
+    app.add_handler(CommandHandler("route_distance",
+                                   cmd_route_distance))
+    app.add_handler(CommandHandler("soft_distance",
+                                   cmd_soft_distance))
+    app.add_handler(CommandHandler("clear_route",
+                                   cmd_clear_route))
+    app.add_handler(CommandHandler("set_home",
+                                   cmd_set_home))
+    app.add_handler(CommandHandler("nearest",
+                                   cmd_nearest))
+    app.add_handler(CommandHandler("set_radius",
+                                   cmd_set_radius))
+    app.add_handler(CommandHandler("filter_area",
+                                   cmd_filter_area))
+    app.add_handler(CommandHandler("max_per_hour",
+                                   cmd_max_per_hour))
+    app.add_handler(CommandHandler("no_updates_every",
+                                   cmd_no_updates_every))
+    app.add_handler(CommandHandler("timezone",
+                                   cmd_timezone))
+    app.add_handler(CommandHandler("coordinates",
+                                   cmd_coordinates))
+    app.add_handler(CommandHandler("units",
+                                   cmd_units))
+    app.add_handler(CommandHandler("admin_restore_backup",
+                                   cmd_admin_restore_backup))
+    app.add_handler(CommandHandler("admin_send_test",
+                                   cmd_admin_send_test))
+    app.add_handler(CommandHandler("admin_ban",
+                                   cmd_admin_ban))
+    app.add_handler(CommandHandler("admin_unban",
+                                   cmd_admin_unban))
+    app.add_handler(CommandHandler("admin_audit",
+                                   cmd_admin_audit))
+    app.add_handler(CommandHandler("admin_simulate",
+                                   cmd_admin_simulate))
+    app.add_handler(CommandHandler("admin_segment",
+                                   cmd_admin_segment))
+    app.add_handler(CommandHandler("admin_stats",
+                                   cmd_admin_stats))
+    app.add_handler(CommandHandler("admin_quiet",
+                                   cmd_admin_quiet))
+    app.add_handler(CommandHandler("admin_set_interval",
+                                   cmd_admin_set_interval))
+    app.add_handler(CommandHandler("admin_config",
+                                   cmd_admin_config))
+    app.add_handler(CommandHandler("admin_cancel",
+                                   cmd_admin_cancel))
+    app.add_handler(CommandHandler("broadcast",
+                                   cmd_broadcast))
+    app.add_handler(CommandHandler("snooze",
+                                   cmd_snooze))
+    app.add_handler(CommandHandler("settings",
+                                   cmd_settings))
+    app.add_handler(CommandHandler("language",
+                                   cmd_language))
+    for key, pref in PREFERENCES.items():
+        if pref.command:
+            app.add_handler(CommandHandler(pref.command,
+                                           generate_toggle_command(key)))
+    app.add_handler(CallbackQueryHandler(on_mute_button,
+                                         pattern="^mute:"))
+    app.add_handler(CallbackQueryHandler(on_snooze_button,
+                                         pattern="^snooze:"))
+    app.add_handler(CallbackQueryHandler(on_settings_button,
+                                         pattern="^pref:"))
+    app.add_handler(CallbackQueryHandler(on_language_button,
+                                         pattern="^language:"))
+    app.add_handler(CallbackQueryHandler(on_cancel_broadcast_button,
+                                         pattern="^broadcast:cancel$"))
+    app.add_handler(MessageHandler(filters.Document.FileExtension("gpx"),
+                                   on_gpx_upload))
+    app.add_handler(MessageHandler(filters.LOCATION,
+                                   on_location))
+    app.add_handler(InlineQueryHandler(on_inline_query))
 
-    trigger = CronTrigger(
-        year="*", month="*", day="*", hour="*", minute="0", second="0"
-    )
+    trigger = generate_check_trigger(configs)
 
     scheduler = AsyncIOScheduler()
     scheduler.start()
+    app.bot_data["scheduler"] = scheduler
+    schedule_quiet_release(app)
 
-    scheduler.add_job(
+    app.bot_data["check_job"] = scheduler.add_job(
         check_for_updates,
         trigger=trigger,
         args=[app],
         name="get_velox_list",
     )
+    scheduler.add_job(
+        announce_new_preferences,
+        args=[app],
+        name="announce_new_preferences",
+    )
+    # check right away instead of waiting for the first tick
+    scheduler.add_job(
+        check_for_updates,
+        args=[app],
+        kwargs={"trigger": "startup"},
+        name="startup_check",
+    )
+
+    scheduler.add_job(
+        watch_dispatcher,
+        trigger=CronTrigger(minute="*/5"),
+        args=[app],
+        name="watch_dispatcher",
+    )
+
+    scheduler.add_job(
+        backup_state,
+        trigger=CronTrigger(hour="3", minute="0"),
+        name="backup_state",
+    )
+
+    scheduler.add_job(
+        purge_old_data,
+        trigger=CronTrigger(hour="3", minute="30"),
+        args=[app],
+        name="purge_old_data",
+    )
+
+    if configs.get("HTTP_PORT"):
+        start_http_server(int(configs["HTTP_PORT"]), configs, lambda: get_health(app),
+                          get_changes_since, f'{STATE_DIR}/poi',
+                          {name: content_type for name, (content_type, _) in POI_EXPORTS.items()})
 
     app.run_polling()
 
@@ -305,15 +4144,61 @@ parser.add_argument('-s', '--save-list', action='store_true',
                     help='[CLI] Save list when performing an update check')
 parser.add_argument('-p', '--print-list', action='store_true',
                     help='[CLI] Print the current list')
+parser.add_argument('--profile',
+                    help='Use the config and state of profiles/PROFILE (e.g. dev, staging)')
+parser.add_argument('--offline', metavar='HTML_FILE',
+                    help='Read the velox list from a local HTML file instead of the website')
+parser.add_argument('--replay', metavar='DIR',
+                    help='[CLI] Replay the HTML snapshots in DIR in order (dry-run)')
+parser.add_argument('--export-chats', metavar='FILE',
+                    help='[CLI] Export the subscribed chats and their preferences to FILE')
+parser.add_argument('--import-chats', metavar='FILE',
+                    help='[CLI] Merge the chats exported in FILE into the subscribed ones')
+parser.add_argument('--log-json', action='store_true',
+                    help='Log one JSON object per line, e.g. for log aggregation')
 args = parser.parse_args()
 
+if args.profile:
+    STATE_DIR = os.path.join(BASE_DIR, 'profiles', args.profile)
+    os.makedirs(STATE_DIR, exist_ok=True)
+
+profile_configs = load_configs()
+# e.g. DEBUG for a dev profile, from its config.json
+setup_logging(args.log_json,
+              os.environ.get("VELOX_LOG_LEVEL", profile_configs.get("LOG_LEVEL", "INFO")))
+# opt-in, needs the sentry-sdk package. The environment variable wins like the VELOX_ ones
+sentry_dsn = os.environ.get("SENTRY_DSN", profile_configs.get("SENTRY_DSN"))
+if sentry_dsn:
+    setup_error_reporting(sentry_dsn)
+OFFLINE_FIXTURE = args.offline
+
+if args.profile:
+    state_log.info(f"Using profile {args.profile}")
+
+migrate_velox_names()
+migrate_preferences()
+
 if args.telegram_bot:
     bot_start()
     sys.exit(0)
 
 # cli section
-asyncio.run(check_for_updates(save_list=args.save_list))
+apply_overrides(load_configs())
+
+if args.replay:
+    replay_snapshots(args.replay)
+    sys.exit(0)
+
+if args.export_chats:
+    export_chats(args.export_chats)
+    sys.exit(0)
+
+if args.import_chats:
+    import_chats(args.import_chats)
+    sys.exit(0)
+
+asyncio.run(check_for_updates(save_list=args.save_list, trigger="cli"))
 if args.print_list:
     print("\nCurrent list:")
-    for velox, lat_long_t in fetch_current_dict().items():
+    for velox, lat_long_t in asyncio.run(fetch_current_dict()).items():
         print(f"{velox}: {generate_maps_base_url(lat_long_t)}")